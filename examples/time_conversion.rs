@@ -19,7 +19,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example 2: Convert Julian date to calendar date
     println!("Example 2: Julian to Calendar Date");
-    let jd2 = JulianDate::new(2460327.0); // January 15, 2024
+    let jd2 = JulianDate::new(2460325.0); // January 15, 2024
     let cal2 = jd2.to_calendar();
     println!("  Julian Date: {:.6}", jd2.as_f64());
     println!(
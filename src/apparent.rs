@@ -0,0 +1,113 @@
+//! Astrometric and apparent geocentric positions and ecliptic coordinates
+
+use crate::ephemeris::{Ephemeris, Position, State};
+use crate::frames::{self, Matrix3};
+use crate::time::JulianDate;
+use crate::Result;
+
+/// Speed of light (km/s), used when the header does not provide CLIGHT
+pub(crate) const SPEED_OF_LIGHT: f64 = 299792.458;
+
+/// Astronomical unit (km), used when the header does not provide AU
+pub(crate) const ASTRONOMICAL_UNIT: f64 = 149597870.7;
+
+/// Ecliptic coordinates of a body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EclipticCoordinates {
+    /// Ecliptic longitude (degrees, 0-360)
+    pub longitude: f64,
+    /// Ecliptic latitude (degrees)
+    pub latitude: f64,
+    /// Distance (AU)
+    pub distance: f64,
+}
+
+/// Astrometric geocentric position of a body, corrected for light time
+///
+/// # Returns
+/// Position relative to the Earth in AU, ICRF axes
+pub fn astrometric(eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<Position> {
+    let earth = eph.get_state("Earth", jd)?;
+    light_time_corrected(eph, body_name, jd, &earth)
+}
+
+/// Apparent geocentric position of a body: light time and annual
+/// aberration applied, GCRS axes (AU)
+pub fn apparent(eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<Position> {
+    let earth = eph.get_state("Earth", jd)?;
+    let astrometric = light_time_corrected(eph, body_name, jd, &earth)?;
+    Ok(aberration(eph, &astrometric, &earth.velocity))
+}
+
+/// Apparent geocentric ecliptic coordinates referred to the true ecliptic
+/// and equinox of date
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::apparent::ecliptic_of_date;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let jd = JulianDate::from_calendar(2024, 3, 20, 3, 6, 0.0)?;
+/// let sun = ecliptic_of_date(&eph, "Sun", jd)?;
+/// println!("Solar longitude: {:.4}", sun.longitude);
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn ecliptic_of_date(
+    eph: &Ephemeris,
+    body_name: &str,
+    jd: JulianDate,
+) -> Result<EclipticCoordinates> {
+    let position = apparent(eph, body_name, jd)?;
+    let ecliptic = ecliptic_of_date_matrix(eph, jd)?.apply(&position);
+    let distance = ecliptic.distance();
+
+    Ok(EclipticCoordinates {
+        longitude: ecliptic.y.atan2(ecliptic.x).to_degrees().rem_euclid(360.0),
+        latitude: (ecliptic.z / distance).asin().to_degrees(),
+        distance,
+    })
+}
+
+/// Rotation from GCRS axes to the true ecliptic and equinox of date
+pub(crate) fn ecliptic_of_date_matrix(eph: &Ephemeris, jd: JulianDate) -> Result<Matrix3> {
+    let eps = frames::mean_obliquity(jd.jd);
+    let (dpsi, deps) = frames::nutation_angles(eph, jd)?;
+    Ok(frames::rotation_x(eps + deps)
+        .mul(&frames::nutation_matrix(eps, dpsi, deps))
+        .mul(&frames::precession_matrix(jd.jd)))
+}
+
+/// Speed of light in AU/day
+pub(crate) fn speed_of_light(eph: &Ephemeris) -> f64 {
+    let clight = eph.get_constant("CLIGHT").unwrap_or(SPEED_OF_LIGHT);
+    let au = eph.get_constant("AU").unwrap_or(ASTRONOMICAL_UNIT);
+    clight * 86400.0 / au
+}
+
+/// Position of a body relative to an observer, with the body evaluated at
+/// the time its light left it
+///
+/// Within one light time of the start of the ephemeris the emission time is
+/// held at its first date, so that queries may run up to its bounds.
+pub(crate) fn light_time_corrected(
+    eph: &Ephemeris,
+    body_name: &str,
+    jd: JulianDate,
+    observer: &State,
+) -> Result<Position> {
+    let c = speed_of_light(eph);
+    let (first, _) = eph.get_date_range();
+    let mut relative = eph.get_position(body_name, jd)? - observer.position;
+    for _ in 0..3 {
+        let emitted = JulianDate::new((jd.jd - relative.distance() / c).max(first));
+        relative = eph.get_position(body_name, emitted)? - observer.position;
+    }
+    Ok(relative)
+}
+
+/// Apply first-order aberration for an observer moving with `velocity` (AU/day)
+pub(crate) fn aberration(eph: &Ephemeris, position: &Position, velocity: &Position) -> Position {
+    let u = position.unit();
+    let beta = *velocity * (1.0 / speed_of_light(eph));
+    (u + beta - u * u.dot(&beta)).unit() * position.distance()
+}
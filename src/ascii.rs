@@ -0,0 +1,429 @@
+//! JPL ASCII ephemeris distribution
+//!
+//! JPL distributes each DE as a text header (`header.441`) and data files
+//! (`ascp01950.441`, ...) holding one block of Chebyshev coefficients per
+//! record, written with Fortran `D` exponents. This module parses both and
+//! assembles them into the binary format read by [`Ephemeris::open`].
+//!
+//! [`Ephemeris::open`]: crate::Ephemeris::open
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+
+use crate::ephemeris::parse_fortran_f64;
+use crate::export::{write_doubles, BinaryHeader, POINTERS};
+use crate::{Error, FileKind, Result};
+
+/// Slack allowed between the end of one record and the start of the next
+/// (days)
+const CONTIGUITY_TOLERANCE: f64 = 1e-6;
+
+/// Group number, line number of its `GROUP` line, and its numbered data
+/// lines
+type Group<'a> = (&'a str, usize, Vec<(usize, &'a str)>);
+
+/// Contents of an ASCII header file
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiHeader {
+    /// Number of coefficients per record
+    pub ncoeff: usize,
+    /// Title lines of group 1010
+    pub titles: Vec<String>,
+    /// Start and end Julian dates (TDB)
+    pub range: (f64, f64),
+    /// Record length (days)
+    pub interval: f64,
+    /// Constant names and values in file order (groups 1040 and 1041)
+    pub constants: Vec<(String, f64)>,
+    /// Offset, coefficient count and sub-interval count of each body
+    /// (group 1050)
+    pub pointers: Vec<[i32; 3]>,
+}
+
+impl AsciiHeader {
+    /// Read a header file
+    pub fn read(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::from(e).in_file(path))?;
+        Self::parse(&text).map_err(|e| e.in_file(path))
+    }
+
+    /// Parse the text of a header file
+    ///
+    /// The header is a sequence of `GROUP nnnn` blocks whose data spans the
+    /// following lines, preceded by a line giving `KSIZE` and `NCOEFF`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut ncoeff = 0;
+        let mut groups: Vec<Group> = Vec::new();
+        for (number, line) in text.lines().enumerate().map(|(k, l)| (k + 1, l)) {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("GROUP") => groups.push((parts.next().unwrap_or(""), number, Vec::new())),
+                Some(_) => match groups.last_mut() {
+                    Some((_, _, lines)) => lines.push((number, line)),
+                    None => {
+                        let tokens: Vec<&str> = line.split_whitespace().collect();
+                        for pair in tokens.windows(2) {
+                            if pair[0] == "NCOEFF=" {
+                                ncoeff = pair[1].parse().map_err(|_| Error::Parse {
+                                    kind: FileKind::AsciiHeader,
+                                    line: number,
+                                    group: String::new(),
+                                    message: format!("invalid NCOEFF '{}'", pair[1]),
+                                })?;
+                            }
+                        }
+                    }
+                },
+                None => {}
+            }
+        }
+        if ncoeff == 0 {
+            return Err(Error::Parse {
+                kind: FileKind::AsciiHeader,
+                line: 1,
+                group: String::new(),
+                message: "NCOEFF not found".to_string(),
+            });
+        }
+
+        let mut header = Self {
+            ncoeff,
+            titles: Vec::new(),
+            range: (0.0, 0.0),
+            interval: 0.0,
+            constants: Vec::new(),
+            pointers: Vec::new(),
+        };
+        let mut names: Vec<&str> = Vec::new();
+        let mut values: Vec<f64> = Vec::new();
+
+        for (group, start, lines) in &groups {
+            let invalid = |line: usize, message: String| Error::Parse {
+                kind: FileKind::AsciiHeader,
+                line,
+                group: group.to_string(),
+                message,
+            };
+            let tokens: Vec<(usize, &str)> = lines
+                .iter()
+                .flat_map(|(n, l)| l.split_whitespace().map(move |t| (*n, t)))
+                .collect();
+            let number = |(line, token): (usize, &str)| {
+                parse_fortran_f64(token)
+                    .ok_or_else(|| invalid(line, format!("invalid number '{}'", token)))
+            };
+            match *group {
+                "1010" => header.titles = lines.iter().map(|(_, l)| l.trim().to_string()).collect(),
+                "1030" => match tokens[..] {
+                    [start, end, interval, ..] => {
+                        header.range = (number(start)?, number(end)?);
+                        header.interval = number(interval)?;
+                    }
+                    _ => {
+                        return Err(invalid(
+                            *start,
+                            "expected start, end and interval".to_string(),
+                        ))
+                    }
+                },
+                "1040" => names = tokens.iter().skip(1).map(|(_, t)| *t).collect(),
+                "1041" => {
+                    values = tokens
+                        .iter()
+                        .skip(1)
+                        .map(|t| number(*t))
+                        .collect::<Result<_>>()?
+                }
+                "1050" => {
+                    let columns = tokens.len() / 3;
+                    if columns == 0 || 3 * columns != tokens.len() {
+                        return Err(invalid(
+                            *start,
+                            "expected three rows of coefficient pointers".to_string(),
+                        ));
+                    }
+                    let cells = tokens
+                        .iter()
+                        .map(|(line, token)| {
+                            token
+                                .parse::<i32>()
+                                .map_err(|_| invalid(*line, format!("invalid pointer '{}'", token)))
+                        })
+                        .collect::<Result<Vec<i32>>>()?;
+                    header.pointers = (0..columns)
+                        .map(|col| [cells[col], cells[columns + col], cells[2 * columns + col]])
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        header.constants = names
+            .iter()
+            .zip(values)
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        Ok(header)
+    }
+
+    /// Get a constant by name
+    pub fn constant(&self, name: &str) -> Option<f64> {
+        self.constants
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Iterator over the coefficient blocks of an ASCII data file
+///
+/// Each block starts with a line holding its number and NCOEFF, followed by
+/// the coefficients three to a line; the last line is padded with zeros.
+pub struct AsciiBlocks<R> {
+    lines: std::io::Lines<R>,
+    ncoeff: usize,
+    line: usize,
+}
+
+impl<R: BufRead> AsciiBlocks<R> {
+    /// Read blocks of `ncoeff` coefficients
+    pub fn new(reader: R, ncoeff: usize) -> Self {
+        Self {
+            lines: reader.lines(),
+            ncoeff,
+            line: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> Option<Result<String>> {
+        self.line += 1;
+        self.lines.next().map(|line| line.map_err(Error::from))
+    }
+
+    fn read_block(&mut self, first: &str) -> Result<Vec<f64>> {
+        let invalid = |line: usize, msg: &str| Error::Parse {
+            kind: FileKind::AsciiData,
+            line,
+            group: String::new(),
+            message: msg.to_string(),
+        };
+        let count = first.split_whitespace().nth(1).and_then(|n| n.parse().ok());
+        if count != Some(self.ncoeff) {
+            return Err(invalid(
+                self.line,
+                &format!("expected a block header with NCOEFF {}", self.ncoeff),
+            ));
+        }
+
+        let mut coefficients = Vec::with_capacity(self.ncoeff + 2);
+        while coefficients.len() < self.ncoeff {
+            let line = self
+                .next_line()
+                .ok_or_else(|| invalid(self.line, "block ends early"))??;
+            for token in line.split_whitespace() {
+                let value =
+                    parse_fortran_f64(token).ok_or_else(|| invalid(self.line, "invalid number"))?;
+                coefficients.push(value);
+            }
+        }
+        coefficients.truncate(self.ncoeff);
+        Ok(coefficients)
+    }
+}
+
+impl<R: BufRead> Iterator for AsciiBlocks<R> {
+    type Item = Result<Vec<f64>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.next_line()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+            if !line.trim().is_empty() {
+                return Some(self.read_block(&line));
+            }
+        }
+    }
+}
+
+/// Assemble a binary ephemeris from a header and ASCII data files
+///
+/// Data files must be given in chronological order. Records repeated where
+/// consecutive files overlap are written once, and a gap between records
+/// is an error. The header of the output covers the records written.
+/// Returns the number of records.
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::ascii::convert;
+/// let records = convert(
+///     "header.440",
+///     &["ascp01950.440", "ascp02050.440"],
+///     "de440_1950_2150.bin",
+/// )?;
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn convert(header_path: &str, data_paths: &[&str], output: &str) -> Result<usize> {
+    let ascii = AsciiHeader::read(header_path)?;
+    let mut pointers = [[0i32; 3]; POINTERS];
+    for (pointer, source) in pointers.iter_mut().zip(&ascii.pointers) {
+        *pointer = *source;
+    }
+    let mut titles = ascii
+        .titles
+        .iter()
+        .cloned()
+        .chain(std::iter::repeat(String::new()));
+    let mut header = BinaryHeader {
+        titles: [
+            titles.next().unwrap_or_default(),
+            titles.next().unwrap_or_default(),
+            titles.next().unwrap_or_default(),
+        ],
+        constants: ascii.constants.clone(),
+        range: [ascii.range.0, ascii.range.1, ascii.interval],
+        au: ascii.constant("AU").unwrap_or(0.0),
+        emrat: ascii.constant("EMRAT").unwrap_or(0.0),
+        denum: ascii.constant("DENUM").unwrap_or(0.0) as i32,
+        pointers,
+    };
+    let ncoeff = header.record_length(ascii.ncoeff);
+
+    let in_output = |err: std::io::Error| Error::from(err).in_file(output);
+    let mut out = BufWriter::new(File::create(output).map_err(in_output)?);
+    out.write_all(&vec![0u8; 2 * ncoeff * 8])
+        .map_err(in_output)?;
+
+    let mut span: Option<(f64, f64)> = None;
+    let mut records = 0;
+    for path in data_paths {
+        let file = File::open(path).map_err(|e| Error::from(e).in_file(path))?;
+        for block in AsciiBlocks::new(BufReader::new(file), ascii.ncoeff) {
+            let mut record = block.map_err(|e| e.in_file(path))?;
+            let (start, end) = (record[0], record[1]);
+            if let Some((_, last)) = span {
+                if start < last - CONTIGUITY_TOLERANCE {
+                    continue;
+                }
+                if start > last + CONTIGUITY_TOLERANCE {
+                    return Err(Error::Ephemeris(format!(
+                        "Gap between Julian dates {} and {}",
+                        last, start
+                    ))
+                    .in_file(path));
+                }
+            }
+            span = Some((span.map_or(start, |(first, _)| first), end));
+            record.resize(ncoeff, 0.0);
+            write_doubles(&mut out, &record).map_err(|e| e.in_file(output))?;
+            records += 1;
+        }
+    }
+
+    let (start, end) = span
+        .ok_or_else(|| Error::Ephemeris("No records found in the ASCII data files".to_string()))?;
+    header.range[0] = start;
+    header.range[1] = end;
+    let mut file = out
+        .into_inner()
+        .map_err(|err| in_output(err.into_error()))?;
+    file.seek(SeekFrom::Start(0)).map_err(in_output)?;
+    file.write_all(&header.encode(ncoeff)).map_err(in_output)?;
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ephemeris, JulianDate};
+
+    const AU: f64 = 149597870.7;
+
+    fn header_text() -> String {
+        let mut pointers = [[0i32; 3]; 13];
+        pointers[10] = [3, 2, 1];
+        let row = |k: usize| {
+            pointers
+                .iter()
+                .map(|p| format!("{:6}", p[k]))
+                .collect::<String>()
+        };
+        format!(
+            "KSIZE=    16    NCOEFF=     8\n\n\
+             GROUP   1010\n\nJPL Planetary Ephemeris DE999/LE999\nStart Epoch: JED=  2451536.5\n\n\
+             GROUP   1030\n\n  2451536.50  2451600.50  32.\n\n\
+             GROUP   1040\n\n     3\n  DENUM   AU      EMRAT\n\n\
+             GROUP   1041\n\n     3\n  0.999D+03  0.1495978707D+09  0.813D+02\n\n\
+             GROUP   1050\n\n{}\n{}\n{}\n",
+            row(0),
+            row(1),
+            row(2)
+        )
+    }
+
+    fn block(number: usize, start: f64, x: f64) -> String {
+        let values = [start, start + 32.0, x, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let lines: Vec<String> = values
+            .chunks(3)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|v| format!("{:26.18E}", v).replace('E', "D"))
+                    .collect()
+            })
+            .collect();
+        format!("{:6}{:6}\n{}\n", number, 8, lines.join("\n"))
+    }
+
+    #[test]
+    fn test_convert() {
+        let header = AsciiHeader::parse(&header_text()).unwrap();
+        assert_eq!(header.ncoeff, 8);
+        assert_eq!(header.range, (2451536.5, 2451600.5));
+        assert_eq!(header.constant("EMRAT"), Some(81.3));
+        assert_eq!(header.pointers[10], [3, 2, 1]);
+
+        let dir = std::env::temp_dir().join(format!("rust_jpl_ascii_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        std::fs::write(path("header.999"), header_text()).unwrap();
+        std::fs::write(path("a.999"), block(1, 2451536.5, AU)).unwrap();
+        let overlapping = block(1, 2451536.5, AU) + &block(2, 2451568.5, 2.0 * AU);
+        std::fs::write(path("b.999"), overlapping).unwrap();
+        std::fs::write(path("c.999"), block(1, 2451632.5, AU)).unwrap();
+
+        let records = convert(
+            &path("header.999"),
+            &[&path("a.999"), &path("b.999")],
+            &path("de999.bin"),
+        )
+        .unwrap();
+        assert_eq!(records, 2);
+        let eph = Ephemeris::open(&path("de999.bin")).unwrap();
+        assert_eq!(eph.get_metadata().ephemeris_number, 999);
+        let sun = eph.get_position("Sun", JulianDate::new(2451580.0)).unwrap();
+        assert!((sun.x - 2.0).abs() < 1e-15);
+
+        let gap = convert(
+            &path("header.999"),
+            &[&path("a.999"), &path("c.999")],
+            &path("gap.bin"),
+        );
+        assert!(matches!(gap, Err(Error::File { path: file, .. }) if file == path("c.999")));
+
+        std::fs::write(path("d.999"), block(1, 2451536.5, AU).replace("0.0", "x")).unwrap();
+        let invalid = convert(&path("header.999"), &[&path("d.999")], &path("bad.bin"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let err = invalid.err().unwrap();
+        assert!(matches!(&err, Error::File { path: file, .. } if *file == path("d.999")));
+        assert!(matches!(
+            err.root(),
+            Error::Parse {
+                kind: FileKind::AsciiData,
+                line: 3,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,581 @@
+//! Zodiac longitudes, house cusps and aspects
+//!
+//! Tropical longitudes are apparent geocentric ecliptic longitudes of date.
+//! Sidereal longitudes subtract an ayanamsa, defined by its value at a
+//! reference epoch and carried to other dates with the IAU 2006 general
+//! precession in longitude. All angles in this module are in degrees.
+
+use crate::apparent;
+use crate::ephemeris::Ephemeris;
+use crate::frames;
+use crate::observer::Observer;
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Time step used to decide whether an aspect is applying (days)
+const APPLYING_STEP: f64 = 0.01;
+
+/// Iterations allowed for the Placidus cusp solution
+const PLACIDUS_ITERATIONS: usize = 50;
+
+/// Sign of the tropical or sidereal zodiac
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZodiacSign {
+    Aries,
+    Taurus,
+    Gemini,
+    Cancer,
+    Leo,
+    Virgo,
+    Libra,
+    Scorpio,
+    Sagittarius,
+    Capricorn,
+    Aquarius,
+    Pisces,
+}
+
+impl ZodiacSign {
+    const ALL: [ZodiacSign; 12] = [
+        ZodiacSign::Aries,
+        ZodiacSign::Taurus,
+        ZodiacSign::Gemini,
+        ZodiacSign::Cancer,
+        ZodiacSign::Leo,
+        ZodiacSign::Virgo,
+        ZodiacSign::Libra,
+        ZodiacSign::Scorpio,
+        ZodiacSign::Sagittarius,
+        ZodiacSign::Capricorn,
+        ZodiacSign::Aquarius,
+        ZodiacSign::Pisces,
+    ];
+
+    /// Sign containing an ecliptic longitude
+    pub fn from_longitude(longitude: f64) -> Self {
+        Self::ALL[(longitude.rem_euclid(360.0) / 30.0) as usize % 12]
+    }
+}
+
+/// Longitude expressed as a zodiac sign and degree within the sign
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZodiacPosition {
+    /// Ecliptic longitude (0-360)
+    pub longitude: f64,
+    /// Sign containing the longitude
+    pub sign: ZodiacSign,
+    /// Degrees past the start of the sign (0-30)
+    pub degree: f64,
+}
+
+impl ZodiacPosition {
+    /// Split an ecliptic longitude into sign and degree
+    pub fn from_longitude(longitude: f64) -> Self {
+        let longitude = longitude.rem_euclid(360.0);
+        Self {
+            longitude,
+            sign: ZodiacSign::from_longitude(longitude),
+            degree: longitude.rem_euclid(30.0),
+        }
+    }
+}
+
+/// Offset between the tropical and sidereal zodiacs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ayanamsa {
+    /// Lahiri (Chitrapaksha), the Indian national standard
+    Lahiri,
+    /// Fagan-Bradley, common in western sidereal astrology
+    FaganBradley,
+    /// B. V. Raman
+    Raman,
+    /// Krishnamurti Paddhati
+    Krishnamurti,
+    /// User-defined ayanamsa
+    Custom {
+        /// Reference epoch (Julian date, TT)
+        epoch: f64,
+        /// Ayanamsa at the reference epoch (degrees)
+        value: f64,
+    },
+}
+
+impl Ayanamsa {
+    /// Reference epoch and value, as tabulated by the Swiss Ephemeris
+    fn reference(&self) -> (f64, f64) {
+        match *self {
+            Ayanamsa::Lahiri => (2435553.5, 23.245524743),
+            Ayanamsa::FaganBradley => (2433282.42346, 24.042044444),
+            Ayanamsa::Raman => (2415020.0, 21.014444),
+            Ayanamsa::Krishnamurti => (2415020.0, 22.363889),
+            Ayanamsa::Custom { epoch, value } => (epoch, value),
+        }
+    }
+
+    /// Value of the ayanamsa at a date (degrees)
+    pub fn value(&self, jd: JulianDate) -> f64 {
+        let (epoch, value) = self.reference();
+        value + general_precession(jd.jd) - general_precession(epoch)
+    }
+}
+
+/// House system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HouseSystem {
+    /// Trisection of the diurnal and nocturnal semi-arcs
+    Placidus,
+    /// Birthplace system: trisection of the Midheaven's diurnal semi-arc
+    Koch,
+    /// 30 degree houses starting at the Ascendant
+    Equal,
+    /// Each house is a whole sign, starting with the Ascendant's sign
+    WholeSign,
+}
+
+/// House cusps and angles of a chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HouseCusps {
+    /// House system used
+    pub system: HouseSystem,
+    /// Ecliptic longitudes of the cusps of houses 1 to 12
+    pub cusps: [f64; 12],
+    /// Ascendant
+    pub ascendant: f64,
+    /// Midheaven (Medium Coeli)
+    pub midheaven: f64,
+}
+
+impl HouseCusps {
+    /// House (1-12) containing an ecliptic longitude
+    pub fn house_of(&self, longitude: f64) -> usize {
+        let longitude = longitude.rem_euclid(360.0);
+        (0..12)
+            .find(|&i| {
+                let start = self.cusps[i];
+                let width = (self.cusps[(i + 1) % 12] - start).rem_euclid(360.0);
+                (longitude - start).rem_euclid(360.0) < width
+            })
+            .map_or(1, |i| i + 1)
+    }
+}
+
+/// Aspect between two ecliptic longitudes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectKind {
+    /// 0 degrees
+    Conjunction,
+    /// 30 degrees
+    SemiSextile,
+    /// 60 degrees
+    Sextile,
+    /// 90 degrees
+    Square,
+    /// 120 degrees
+    Trine,
+    /// 150 degrees
+    Quincunx,
+    /// 180 degrees
+    Opposition,
+}
+
+impl AspectKind {
+    const ALL: [AspectKind; 7] = [
+        AspectKind::Conjunction,
+        AspectKind::SemiSextile,
+        AspectKind::Sextile,
+        AspectKind::Square,
+        AspectKind::Trine,
+        AspectKind::Quincunx,
+        AspectKind::Opposition,
+    ];
+
+    /// Exact angle of the aspect (degrees)
+    pub fn angle(&self) -> f64 {
+        match self {
+            AspectKind::Conjunction => 0.0,
+            AspectKind::SemiSextile => 30.0,
+            AspectKind::Sextile => 60.0,
+            AspectKind::Square => 90.0,
+            AspectKind::Trine => 120.0,
+            AspectKind::Quincunx => 150.0,
+            AspectKind::Opposition => 180.0,
+        }
+    }
+}
+
+/// Largest deviation from the exact angle accepted for each aspect
+/// (degrees); a negative orb disables the aspect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AspectOrbs {
+    pub conjunction: f64,
+    pub semi_sextile: f64,
+    pub sextile: f64,
+    pub square: f64,
+    pub trine: f64,
+    pub quincunx: f64,
+    pub opposition: f64,
+}
+
+impl Default for AspectOrbs {
+    fn default() -> Self {
+        Self {
+            conjunction: 8.0,
+            semi_sextile: 2.0,
+            sextile: 6.0,
+            square: 7.0,
+            trine: 8.0,
+            quincunx: 3.0,
+            opposition: 8.0,
+        }
+    }
+}
+
+impl AspectOrbs {
+    /// Orb of an aspect (degrees)
+    pub fn orb(&self, kind: AspectKind) -> f64 {
+        match kind {
+            AspectKind::Conjunction => self.conjunction,
+            AspectKind::SemiSextile => self.semi_sextile,
+            AspectKind::Sextile => self.sextile,
+            AspectKind::Square => self.square,
+            AspectKind::Trine => self.trine,
+            AspectKind::Quincunx => self.quincunx,
+            AspectKind::Opposition => self.opposition,
+        }
+    }
+}
+
+/// Aspect found between two bodies
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aspect {
+    /// First body
+    pub first: String,
+    /// Second body
+    pub second: String,
+    /// Type of aspect
+    pub kind: AspectKind,
+    /// Angular distance in longitude (0-180)
+    pub separation: f64,
+    /// Deviation from the exact aspect angle
+    pub orb: f64,
+    /// Whether the aspect is getting closer to exact
+    pub applying: bool,
+}
+
+/// Tropical zodiac position of a body
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::astrology::{sidereal_longitude, tropical_longitude, Ayanamsa};
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let jd = JulianDate::from_calendar(2024, 1, 15, 12, 0, 0.0)?;
+/// let tropical = tropical_longitude(&eph, "Sun", jd)?;
+/// let sidereal = sidereal_longitude(&eph, "Sun", jd, Ayanamsa::Lahiri)?;
+/// println!("{:?} {:.2} / {:?} {:.2}", tropical.sign, tropical.degree, sidereal.sign, sidereal.degree);
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn tropical_longitude(
+    eph: &Ephemeris,
+    body_name: &str,
+    jd: JulianDate,
+) -> Result<ZodiacPosition> {
+    let ecliptic = apparent::ecliptic_of_date(eph, body_name, jd)?;
+    Ok(ZodiacPosition::from_longitude(ecliptic.longitude))
+}
+
+/// Sidereal zodiac position of a body
+pub fn sidereal_longitude(
+    eph: &Ephemeris,
+    body_name: &str,
+    jd: JulianDate,
+    ayanamsa: Ayanamsa,
+) -> Result<ZodiacPosition> {
+    let tropical = tropical_longitude(eph, body_name, jd)?;
+    Ok(ZodiacPosition::from_longitude(
+        tropical.longitude - ayanamsa.value(jd),
+    ))
+}
+
+/// House cusps for an observer at a date
+///
+/// # Errors
+/// Placidus and Koch houses are undefined inside the polar circles.
+pub fn houses(
+    eph: &Ephemeris,
+    observer: &Observer,
+    jd: JulianDate,
+    system: HouseSystem,
+) -> Result<HouseCusps> {
+    let armc = observer.local_sidereal_time(eph, jd)?.to_degrees();
+    let (_, deps) = frames::nutation_angles(eph, jd)?;
+    let obliquity = (frames::mean_obliquity(jd.jd) + deps).to_degrees();
+    house_cusps(armc, observer.latitude, obliquity, system)
+}
+
+/// House cusps from the sidereal time, latitude and obliquity
+///
+/// # Arguments
+/// * `armc` - Right ascension of the meridian, i.e. local sidereal time (degrees)
+/// * `latitude` - Geographic latitude (degrees)
+/// * `obliquity` - True obliquity of the ecliptic (degrees)
+/// * `system` - House system
+pub fn house_cusps(
+    armc: f64,
+    latitude: f64,
+    obliquity: f64,
+    system: HouseSystem,
+) -> Result<HouseCusps> {
+    let ascendant = ascendant(armc, latitude, obliquity);
+    let midheaven = longitude_of_right_ascension(armc, obliquity);
+
+    let mut cusps = [0.0; 12];
+    match system {
+        HouseSystem::Equal | HouseSystem::WholeSign => {
+            let first = match system {
+                HouseSystem::Equal => ascendant,
+                _ => (ascendant / 30.0).floor() * 30.0,
+            };
+            for (i, cusp) in cusps.iter_mut().enumerate() {
+                *cusp = (first + 30.0 * i as f64).rem_euclid(360.0);
+            }
+        }
+        HouseSystem::Placidus | HouseSystem::Koch => {
+            if latitude.abs() >= 90.0 - obliquity {
+                return Err(Error::Config(format!(
+                    "{:?} houses are undefined at latitude {}",
+                    system, latitude
+                )));
+            }
+            let [c11, c12, c2, c3] = if system == HouseSystem::Placidus {
+                [
+                    placidus_cusp(armc, latitude, obliquity, 1.0 / 3.0, true)?,
+                    placidus_cusp(armc, latitude, obliquity, 2.0 / 3.0, true)?,
+                    placidus_cusp(armc, latitude, obliquity, 2.0 / 3.0, false)?,
+                    placidus_cusp(armc, latitude, obliquity, 1.0 / 3.0, false)?,
+                ]
+            } else {
+                // A third of the Midheaven's ascensional difference
+                let declination =
+                    (obliquity.to_radians().sin() * midheaven.to_radians().sin()).asin();
+                let ad3 = (latitude.to_radians().tan() * declination.tan())
+                    .asin()
+                    .to_degrees()
+                    / 3.0;
+                [
+                    ascendant_of(armc - 60.0 - 2.0 * ad3, latitude, obliquity),
+                    ascendant_of(armc - 30.0 - ad3, latitude, obliquity),
+                    ascendant_of(armc + 30.0 + ad3, latitude, obliquity),
+                    ascendant_of(armc + 60.0 + 2.0 * ad3, latitude, obliquity),
+                ]
+            };
+            cusps[0] = ascendant;
+            cusps[1] = c2;
+            cusps[2] = c3;
+            cusps[9] = midheaven;
+            cusps[10] = c11;
+            cusps[11] = c12;
+            for i in [0, 1, 2, 9, 10, 11] {
+                cusps[(i + 6) % 12] = (cusps[i] + 180.0).rem_euclid(360.0);
+            }
+        }
+    }
+
+    Ok(HouseCusps {
+        system,
+        cusps,
+        ascendant,
+        midheaven,
+    })
+}
+
+/// Aspect formed by two ecliptic longitudes, if any
+///
+/// # Returns
+/// The aspect closest to exact among those within their orbs, with its
+/// deviation from exact
+pub fn aspect_between(
+    longitude_a: f64,
+    longitude_b: f64,
+    orbs: &AspectOrbs,
+) -> Option<(AspectKind, f64)> {
+    let separation = separation(longitude_a, longitude_b);
+    AspectKind::ALL
+        .iter()
+        .map(|&kind| (kind, (separation - kind.angle()).abs()))
+        .filter(|&(kind, deviation)| deviation <= orbs.orb(kind))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Find the aspects between every pair of bodies at a date
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::astrology::{find_aspects, AspectOrbs};
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let jd = JulianDate::from_calendar(2024, 1, 15, 12, 0, 0.0)?;
+/// let bodies = ["Sun", "Moon", "Mercury", "Venus", "Mars", "Jupiter", "Saturn"];
+/// for aspect in find_aspects(&eph, &bodies, jd, &AspectOrbs::default())? {
+///     println!("{} {:?} {} (orb {:.2})", aspect.first, aspect.kind, aspect.second, aspect.orb);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_aspects(
+    eph: &Ephemeris,
+    bodies: &[&str],
+    jd: JulianDate,
+    orbs: &AspectOrbs,
+) -> Result<Vec<Aspect>> {
+    let later = JulianDate::new(jd.jd + APPLYING_STEP);
+    let mut now = Vec::with_capacity(bodies.len());
+    let mut next = Vec::with_capacity(bodies.len());
+    for body in bodies {
+        now.push(apparent::ecliptic_of_date(eph, body, jd)?.longitude);
+        next.push(apparent::ecliptic_of_date(eph, body, later)?.longitude);
+    }
+
+    let mut aspects = Vec::new();
+    for i in 0..bodies.len() {
+        for j in i + 1..bodies.len() {
+            if let Some((kind, orb)) = aspect_between(now[i], now[j], orbs) {
+                let future = (separation(next[i], next[j]) - kind.angle()).abs();
+                aspects.push(Aspect {
+                    first: bodies[i].to_string(),
+                    second: bodies[j].to_string(),
+                    kind,
+                    separation: separation(now[i], now[j]),
+                    orb,
+                    applying: future < orb,
+                });
+            }
+        }
+    }
+    Ok(aspects)
+}
+
+/// IAU 2006 general precession in longitude since J2000 (degrees)
+fn general_precession(jd: f64) -> f64 {
+    let t = frames::centuries_since_j2000(jd);
+    (5028.796195 + (1.1054348 + (0.00007964 + (-0.000023857 - 0.0000000383 * t) * t) * t) * t) * t
+        / 3600.0
+}
+
+/// Angular distance between two longitudes (0-180)
+fn separation(longitude_a: f64, longitude_b: f64) -> f64 {
+    let difference = (longitude_a - longitude_b).rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Ecliptic longitude of the ecliptic point with a given right ascension
+fn longitude_of_right_ascension(right_ascension: f64, obliquity: f64) -> f64 {
+    let ra = right_ascension.to_radians();
+    ra.sin()
+        .atan2(ra.cos() * obliquity.to_radians().cos())
+        .to_degrees()
+        .rem_euclid(360.0)
+}
+
+/// Ecliptic longitude rising on the eastern horizon
+fn ascendant(armc: f64, latitude: f64, obliquity: f64) -> f64 {
+    let (armc, latitude, obliquity) = (
+        armc.to_radians(),
+        latitude.to_radians(),
+        obliquity.to_radians(),
+    );
+    armc.cos()
+        .atan2(-(armc.sin() * obliquity.cos() + latitude.tan() * obliquity.sin()))
+        .to_degrees()
+        .rem_euclid(360.0)
+}
+
+/// Ascendant for a shifted sidereal time, used by the Koch system
+fn ascendant_of(armc: f64, latitude: f64, obliquity: f64) -> f64 {
+    ascendant(armc.rem_euclid(360.0), latitude, obliquity)
+}
+
+/// Placidus cusp dividing a semi-arc in the given fraction, solved by
+/// fixed-point iteration on the cusp's right ascension
+///
+/// Houses 11 and 12 divide the diurnal semi-arc measured from the
+/// Midheaven (`above`); houses 2 and 3 divide the nocturnal semi-arc
+/// measured from the lower meridian.
+fn placidus_cusp(
+    armc: f64,
+    latitude: f64,
+    obliquity: f64,
+    fraction: f64,
+    above: bool,
+) -> Result<f64> {
+    let tan_latitude = latitude.to_radians().tan();
+    let sin_obliquity = obliquity.to_radians().sin();
+    let right_ascension = |ascensional_difference: f64| {
+        if above {
+            armc + fraction * (90.0 + ascensional_difference)
+        } else {
+            armc + 180.0 - fraction * (90.0 - ascensional_difference)
+        }
+    };
+
+    let mut ra = right_ascension(0.0);
+    for _ in 0..PLACIDUS_ITERATIONS {
+        let longitude = longitude_of_right_ascension(ra, obliquity);
+        let declination = (sin_obliquity * longitude.to_radians().sin()).asin();
+        let x = tan_latitude * declination.tan();
+        if x.abs() > 1.0 {
+            return Err(Error::Config(format!(
+                "Placidus cusp does not exist at latitude {}",
+                latitude
+            )));
+        }
+        let next = right_ascension(x.asin().to_degrees());
+        let converged = (next - ra).abs() < 1e-10;
+        ra = next;
+        if converged {
+            break;
+        }
+    }
+    Ok(longitude_of_right_ascension(ra, obliquity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_house_cusps() {
+        // On the equator Placidus and Koch both place the cusps at 30 degree
+        // steps of right ascension from the meridian
+        let placidus = house_cusps(45.0, 0.0, 23.44, HouseSystem::Placidus).unwrap();
+        let koch = house_cusps(45.0, 0.0, 23.44, HouseSystem::Koch).unwrap();
+        for i in 0..12 {
+            assert!((placidus.cusps[i] - koch.cusps[i]).abs() < 1e-9);
+        }
+        assert!((placidus.cusps[10] - longitude_of_right_ascension(75.0, 23.44)).abs() < 1e-9);
+
+        // Mid-latitude chart: Ascendant and Midheaven fixed by the angles
+        let chart = house_cusps(0.0, 51.5, 23.44, HouseSystem::Placidus).unwrap();
+        assert!(chart.midheaven.abs() < 1e-9);
+        assert!(chart.ascendant > 90.0 && chart.ascendant < 180.0);
+        assert_eq!(chart.house_of(chart.ascendant + 1.0), 1);
+        assert_eq!(chart.house_of(chart.midheaven - 1.0), 9);
+
+        assert!(house_cusps(0.0, 70.0, 23.44, HouseSystem::Placidus).is_err());
+        let whole = house_cusps(0.0, 70.0, 23.44, HouseSystem::WholeSign).unwrap();
+        assert_eq!(whole.cusps[0] % 30.0, 0.0);
+    }
+
+    #[test]
+    fn test_aspects_and_ayanamsa() {
+        let orbs = AspectOrbs::default();
+        assert_eq!(
+            aspect_between(355.0, 115.0, &orbs),
+            Some((AspectKind::Trine, 0.0))
+        );
+        assert_eq!(aspect_between(10.0, 55.0, &orbs), None);
+
+        let lahiri = Ayanamsa::Lahiri.value(JulianDate::new(frames::J2000));
+        assert!((lahiri - 23.85).abs() < 0.01);
+        assert_eq!(ZodiacSign::from_longitude(359.9), ZodiacSign::Pisces);
+    }
+}
@@ -0,0 +1,349 @@
+//! Ephemeris construction from paths, readers or in-memory data
+//!
+//! [`EphemerisBuilder`] gathers the binary ephemeris, the optional ASCII
+//! header and initial data files, SPK kernels and an expected checksum from
+//! any mix of `config.toml`, environment variables and code. Settings made
+//! later replace earlier ones, so `config(..)?.env()` lets the environment
+//! override the configuration file. The files named in `config.toml` are
+//! only checked by [`EphemerisBuilder::build`], so a stale path can still
+//! be replaced.
+
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+
+use crate::ascii::AsciiHeader;
+use crate::config::AppConfig;
+use crate::ephemeris::{Ephemeris, Source};
+use crate::{Error, Result};
+
+/// Environment variable overriding the binary ephemeris path
+pub const ENV_EPHEMERIS: &str = "RUST_JPL_EPHEMERIS";
+/// Environment variable overriding the ASCII header path
+pub const ENV_HEADER: &str = "RUST_JPL_HEADER";
+/// Environment variable overriding the initial data path
+pub const ENV_INITIAL_DATA: &str = "RUST_JPL_INITIAL_DATA";
+/// Environment variable overriding the SPK kernels, separated like `PATH`
+pub const ENV_KERNELS: &str = "RUST_JPL_KERNELS";
+
+/// Binary ephemeris data
+enum DataInput {
+    Path(String),
+    Reader(Box<dyn Source>),
+}
+
+impl DataInput {
+    fn path(&self) -> Option<&String> {
+        match self {
+            DataInput::Path(path) => Some(path),
+            DataInput::Reader(_) => None,
+        }
+    }
+}
+
+/// Header or initial data text
+enum TextInput {
+    Path(String),
+    Reader(Box<dyn Read + Send>),
+}
+
+impl TextInput {
+    fn path(&self) -> Option<&String> {
+        match self {
+            TextInput::Path(path) => Some(path),
+            TextInput::Reader(_) => None,
+        }
+    }
+
+    fn read(self) -> Result<String> {
+        let mut text = String::new();
+        match self {
+            TextInput::Path(path) => {
+                text = std::fs::read_to_string(&path).map_err(|e| Error::from(e).in_file(&path))?
+            }
+            TextInput::Reader(mut reader) => {
+                reader.read_to_string(&mut text)?;
+            }
+        }
+        Ok(text)
+    }
+}
+
+/// Step-by-step construction of an [`Ephemeris`]
+///
+/// Only the binary ephemeris is required. Without a header file the date
+/// range, constants and pointers are read from the binary file's own
+/// header records, as in [`Ephemeris::open`]; without an initial data file
+/// every body with coefficients is active except nutations, librations and
+/// TT-TDB.
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::EphemerisBuilder;
+/// let eph = EphemerisBuilder::new()
+///     .ephemeris_path("assets/linux_p1550p2650.440")
+///     .kernel("assets/sb441-n16.bsp")
+///     .env()
+///     .build()?;
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+#[derive(Default)]
+pub struct EphemerisBuilder {
+    ephemeris: Option<DataInput>,
+    header: Option<TextInput>,
+    initial_data: Option<TextInput>,
+    kernels: Vec<String>,
+    sha256: Option<String>,
+    /// Paths read from `config.toml`, checked when building
+    config_paths: Vec<String>,
+}
+
+impl EphemerisBuilder {
+    /// Create a builder with nothing set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the paths, kernels and checksum of a `config.toml`
+    pub fn config(mut self, config_path: &str) -> Result<Self> {
+        let config = AppConfig::read(config_path)?;
+        self.config_paths = config.paths().cloned().collect();
+        self.ephemeris = Some(DataInput::Path(config.nasa_jpl_de441));
+        self.header = config.header_441.map(TextInput::Path);
+        self.initial_data = config.initial_data_dat.map(TextInput::Path);
+        self.kernels = config.kernels;
+        self.sha256 = config.sha256;
+        Ok(self)
+    }
+
+    /// Take the paths set in the `RUST_JPL_EPHEMERIS`, `RUST_JPL_HEADER`,
+    /// `RUST_JPL_INITIAL_DATA` and `RUST_JPL_KERNELS` environment variables
+    ///
+    /// Variables that are unset or empty leave the builder unchanged.
+    pub fn env(self) -> Self {
+        self.env_with(|name| std::env::var(name).ok())
+    }
+
+    /// Like [`env`](Self::env), with the variables looked up by `lookup`
+    /// instead of in the process environment
+    pub fn env_with(mut self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| lookup(name).filter(|v| !v.is_empty());
+        if let Some(path) = var(ENV_EPHEMERIS) {
+            self = self.ephemeris_path(&path);
+        }
+        if let Some(path) = var(ENV_HEADER) {
+            self = self.header_path(&path);
+        }
+        if let Some(path) = var(ENV_INITIAL_DATA) {
+            self = self.initial_data_path(&path);
+        }
+        if let Some(paths) = var(ENV_KERNELS) {
+            self.kernels = std::env::split_paths(&paths)
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+        }
+        self
+    }
+
+    /// Read the binary ephemeris from a file
+    pub fn ephemeris_path(mut self, path: &str) -> Self {
+        self.ephemeris = Some(DataInput::Path(path.to_string()));
+        self
+    }
+
+    /// Read the binary ephemeris from a seekable reader
+    pub fn ephemeris_reader(mut self, reader: impl Read + Seek + Send + 'static) -> Self {
+        self.ephemeris = Some(DataInput::Reader(Box::new(reader)));
+        self
+    }
+
+    /// Read the binary ephemeris from memory, such as an `include_bytes!`
+    /// blob or a `Vec<u8>`
+    pub fn ephemeris_bytes(self, bytes: impl AsRef<[u8]> + Send + 'static) -> Self {
+        self.ephemeris_reader(Cursor::new(bytes))
+    }
+
+    /// Read the ASCII header from a file
+    pub fn header_path(mut self, path: &str) -> Self {
+        self.header = Some(TextInput::Path(path.to_string()));
+        self
+    }
+
+    /// Read the ASCII header from a reader
+    pub fn header_reader(mut self, reader: impl Read + Send + 'static) -> Self {
+        self.header = Some(TextInput::Reader(Box::new(reader)));
+        self
+    }
+
+    /// Read the ASCII header from memory
+    pub fn header_bytes(self, bytes: impl AsRef<[u8]> + Send + 'static) -> Self {
+        self.header_reader(Cursor::new(bytes))
+    }
+
+    /// Read the initial data from a file
+    pub fn initial_data_path(mut self, path: &str) -> Self {
+        self.initial_data = Some(TextInput::Path(path.to_string()));
+        self
+    }
+
+    /// Read the initial data from a reader
+    pub fn initial_data_reader(mut self, reader: impl Read + Send + 'static) -> Self {
+        self.initial_data = Some(TextInput::Reader(Box::new(reader)));
+        self
+    }
+
+    /// Read the initial data from memory
+    pub fn initial_data_bytes(self, bytes: impl AsRef<[u8]> + Send + 'static) -> Self {
+        self.initial_data_reader(Cursor::new(bytes))
+    }
+
+    /// Add an SPK kernel to load
+    pub fn kernel(mut self, path: &str) -> Self {
+        self.kernels.push(path.to_string());
+        self
+    }
+
+    /// Check the SHA-256 of the binary ephemeris when building (requires the
+    /// `checksum` feature)
+    pub fn sha256(mut self, expected: &str) -> Self {
+        self.sha256 = Some(expected.to_string());
+        self
+    }
+
+    /// Load the ephemeris
+    pub fn build(self) -> Result<Ephemeris> {
+        let in_use = self
+            .ephemeris
+            .as_ref()
+            .and_then(DataInput::path)
+            .into_iter()
+            .chain(self.header.as_ref().and_then(TextInput::path))
+            .chain(self.initial_data.as_ref().and_then(TextInput::path))
+            .chain(&self.kernels);
+        for path in in_use.filter(|path| self.config_paths.contains(path)) {
+            AppConfig::validate_file(path)?;
+        }
+
+        let (data, path): (Box<dyn Source>, Option<String>) = match self.ephemeris {
+            Some(DataInput::Path(path)) => {
+                let file = File::open(&path).map_err(|e| Error::from(e).in_file(&path))?;
+                (Box::new(file), Some(path))
+            }
+            Some(DataInput::Reader(reader)) => (reader, None),
+            None => {
+                return Err(Error::Config(format!(
+                    "No ephemeris file given; set one in config.toml or {}",
+                    ENV_EPHEMERIS
+                )))
+            }
+        };
+        let in_data = |err: Error| match &path {
+            Some(path) => err.in_file(path),
+            None => err,
+        };
+
+        let header = match self.header {
+            Some(TextInput::Path(path)) => Some(AsciiHeader::read(&path)?),
+            Some(input) => Some(AsciiHeader::parse(&input.read()?)?),
+            None => None,
+        };
+        let initial_data = self.initial_data.map(TextInput::read).transpose()?;
+
+        let mut eph = Ephemeris::load(data, header, initial_data.as_deref()).map_err(in_data)?;
+        if let Some(expected) = &self.sha256 {
+            #[cfg(feature = "checksum")]
+            eph.verify_sha256(expected).map_err(in_data)?;
+            #[cfg(not(feature = "checksum"))]
+            return Err(Error::Config(format!(
+                "SHA-256 {} was given but rust-jpl was built without the checksum feature",
+                expected
+            )));
+        }
+        for kernel in &self.kernels {
+            eph.load_kernel(kernel)?;
+        }
+        Ok(eph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::write_binary;
+    use crate::JulianDate;
+
+    #[test]
+    fn test_builder_sources() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_build_{}.bin", std::process::id()));
+        write_binary(&path);
+        let bytes = std::fs::read(&path).unwrap();
+        let jd = JulianDate::new(2451545.0);
+
+        let eph = EphemerisBuilder::new()
+            .ephemeris_bytes(bytes.clone())
+            .build()
+            .unwrap();
+        assert!((eph.get_position("Sun", jd).unwrap().x - 1.0).abs() < 1e-15);
+
+        let initial_data = "BODIES:\n\nMoon_geocentric true\nSun false\n\nDATE:\n\nStart_year 1999\nEnd_year 2000\n";
+        let eph = EphemerisBuilder::new()
+            .ephemeris_bytes(bytes.clone())
+            .initial_data_bytes(initial_data)
+            .build()
+            .unwrap();
+        assert_eq!(eph.get_metadata().start_year, 1999);
+        assert!(matches!(
+            eph.get_position("Sun", jd),
+            Err(Error::InactiveBody { .. })
+        ));
+
+        let mut pointers = vec!["0 0 0"; 13];
+        pointers[9] = "9 13 10";
+        pointers[10] = "3 2 1";
+        let row = |k: usize| {
+            pointers
+                .iter()
+                .map(|p| format!(" {}", p.split(' ').nth(k).unwrap()))
+                .collect::<String>()
+        };
+        let header = format!(
+            "KSIZE= 796 NCOEFF= 398\nGROUP 1030\n 2451536.5 2451568.5 32.\n\
+             GROUP 1040\n 3\n DENUM AU EMRAT\nGROUP 1041\n 3\n 0.999D+03 0.1495978707D+09 0.813D+02\n\
+             GROUP 1050\n{}\n{}\n{}\n",
+            row(0),
+            row(1),
+            row(2)
+        );
+        let eph = EphemerisBuilder::new()
+            .ephemeris_bytes(bytes)
+            .header_bytes(header)
+            .build()
+            .unwrap();
+        assert_eq!(eph.get_constant("EMRAT"), Some(81.3));
+        assert!((eph.get_position("Sun", jd).unwrap().x - 1.0).abs() < 1e-15);
+
+        let path = path.to_str().unwrap().to_string();
+        let lookup = |name: &str| (name == ENV_EPHEMERIS).then(|| path.clone());
+        let eph = EphemerisBuilder::new().env_with(lookup).build();
+        assert_eq!(eph.unwrap().get_metadata().ephemeris_number, 999);
+
+        // A configured path that no longer exists can be replaced before
+        // it is checked
+        let config = std::env::temp_dir().join(format!("rust_jpl_{}.toml", std::process::id()));
+        std::fs::write(&config, "[paths]\nephemeris = \"no_such_file.bin\"\n").unwrap();
+        let config = config.to_str().unwrap();
+        let stale = EphemerisBuilder::new().config(config).unwrap().build();
+        assert!(matches!(stale, Err(Error::Config(_))));
+        let eph = EphemerisBuilder::new()
+            .config(config)
+            .unwrap()
+            .env_with(lookup)
+            .build();
+        std::fs::remove_file(config).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(eph.unwrap().get_metadata().ephemeris_number, 999);
+        assert!(matches!(
+            EphemerisBuilder::new().build(),
+            Err(Error::Config(_))
+        ));
+    }
+}
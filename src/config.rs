@@ -10,9 +10,16 @@ use crate::Error;
 
 #[derive(Debug, Deserialize)]
 struct ConfigPaths {
+    #[serde(alias = "ephemeris")]
     nasa_jpl_de441: String,
-    header_441: String,
-    initial_data_dat: String,
+    #[serde(default, alias = "header")]
+    header_441: Option<String>,
+    #[serde(default, alias = "initial_data")]
+    initial_data_dat: Option<String>,
+    #[serde(default)]
+    kernels: Vec<String>,
+    #[serde(default)]
+    sha256: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,8 +31,15 @@ struct ConfigFile {
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub nasa_jpl_de441: String,
-    pub header_441: String,
-    pub initial_data_dat: String,
+    /// ASCII header file, optional when the binary file carries its own
+    /// header records
+    pub header_441: Option<String>,
+    /// Initial data file giving the active bodies, optional
+    pub initial_data_dat: Option<String>,
+    /// Additional SPK kernels to load
+    pub kernels: Vec<String>,
+    /// Expected SHA-256 of the binary ephemeris file
+    pub sha256: Option<String>,
 }
 
 impl AppConfig {
@@ -34,6 +48,15 @@ impl AppConfig {
     /// # Arguments
     /// * `config_path` - Path to the config.toml file
     pub fn new(config_path: &str) -> Result<Self, Error> {
+        let config = Self::read(config_path)?;
+        for path in config.paths() {
+            Self::validate_file(path)?;
+        }
+        Ok(config)
+    }
+
+    /// Read a config file without checking the files it names
+    pub(crate) fn read(config_path: &str) -> Result<Self, Error> {
         let config = Config::builder()
             .add_source(File::with_name(config_path))
             .build()
@@ -43,24 +66,35 @@ impl AppConfig {
             .try_deserialize()
             .map_err(|e| Error::Config(format!("Failed to deserialize config: {}", e)))?;
 
-        let nasa_jpl_de441 = config_file.paths.nasa_jpl_de441.clone();
-        let header_441 = config_file.paths.header_441.clone();
-        let initial_data_dat = config_file.paths.initial_data_dat.clone();
-
-        Self::validate_file(&nasa_jpl_de441)?;
-        Self::validate_file(&header_441)?;
-        Self::validate_file(&initial_data_dat)?;
-
         Ok(Self {
-            nasa_jpl_de441,
-            header_441,
-            initial_data_dat,
+            nasa_jpl_de441: config_file.paths.nasa_jpl_de441,
+            header_441: config_file.paths.header_441,
+            initial_data_dat: config_file.paths.initial_data_dat,
+            kernels: config_file.paths.kernels,
+            sha256: config_file.paths.sha256,
         })
     }
 
-    fn validate_file(file_path: &str) -> Result<(), Error> {
-        fs::metadata(file_path)
+    /// Every file named by the configuration
+    pub(crate) fn paths(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.nasa_jpl_de441)
+            .chain(&self.header_441)
+            .chain(&self.initial_data_dat)
+            .chain(&self.kernels)
+    }
+
+    pub(crate) fn validate_file(file_path: &str) -> Result<(), Error> {
+        let metadata = fs::metadata(file_path)
             .map_err(|_| Error::Config(format!("Required file not found: {}", file_path)))?;
+        if !metadata.is_file() {
+            return Err(Error::Config(format!("Not a regular file: {}", file_path)));
+        }
+        if metadata.len() == 0 {
+            return Err(Error::Config(format!(
+                "Required file is empty: {}",
+                file_path
+            )));
+        }
         Ok(())
     }
 
@@ -71,12 +105,20 @@ impl AppConfig {
 
     /// Read the header file
     pub fn read_header_441(&self) -> Result<Vec<u8>, Error> {
-        read_file(&self.header_441).map_err(Error::from)
+        let path = self
+            .header_441
+            .as_deref()
+            .ok_or_else(|| Error::Config("No header file configured".to_string()))?;
+        read_file(path).map_err(Error::from)
     }
 
     /// Read the initial data file
     pub fn read_initial_data_dat(&self) -> Result<Vec<u8>, Error> {
-        read_file(&self.initial_data_dat).map_err(Error::from)
+        let path = self
+            .initial_data_dat
+            .as_deref()
+            .ok_or_else(|| Error::Config("No initial data file configured".to_string()))?;
+        read_file(path).map_err(Error::from)
     }
 }
 
@@ -0,0 +1,263 @@
+//! Geocentric conjunctions, oppositions and greatest elongations
+//!
+//! Conjunctions and oppositions are defined in apparent ecliptic longitude
+//! of date; elongations and separations are angles between apparent
+//! geocentric directions.
+
+use crate::apparent;
+use crate::ephemeris::Ephemeris;
+use crate::search::{EventFinder, EventKind};
+use crate::time::JulianDate;
+use crate::Result;
+
+/// Sampling step of the searches (days)
+const SEARCH_STEP: f64 = 1.0;
+
+/// Precision of the returned times (days, about 0.01 s)
+const TOLERANCE: f64 = 1e-7;
+
+/// Largest residual accepted for a longitude crossing (degrees); larger
+/// values come from the wrap-around of the difference
+const WRAP_THRESHOLD: f64 = 1.0;
+
+/// Instant at which two bodies reach a given difference in longitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    /// Time of the event (TDB)
+    pub jd: JulianDate,
+    /// Angular separation of the bodies at the event (degrees)
+    pub separation: f64,
+}
+
+/// Local minimum or maximum of the angular separation of two bodies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeparationExtremum {
+    /// Time of the extremum (TDB)
+    pub jd: JulianDate,
+    /// Angular separation at the extremum (degrees)
+    pub separation: f64,
+    /// Whether the separation is a local maximum
+    pub maximum: bool,
+}
+
+/// Side of the Sun on which an elongation occurs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElongationSide {
+    /// East of the Sun: visible in the evening sky
+    East,
+    /// West of the Sun: visible in the morning sky
+    West,
+}
+
+/// Greatest elongation of a body from the Sun
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elongation {
+    /// Time of greatest elongation (TDB)
+    pub jd: JulianDate,
+    /// Angular distance from the Sun (degrees)
+    pub elongation: f64,
+    /// Side of the Sun
+    pub side: ElongationSide,
+}
+
+/// Angular separation between the apparent geocentric directions of two
+/// bodies (degrees)
+pub fn separation(eph: &Ephemeris, body_a: &str, body_b: &str, jd: JulianDate) -> Result<f64> {
+    let a = apparent::apparent(eph, body_a, jd)?.unit();
+    let b = apparent::apparent(eph, body_b, jd)?.unit();
+    // atan2 form stays accurate for small and near-180 degree separations
+    Ok(a.cross(&b).distance().atan2(a.dot(&b)).to_degrees())
+}
+
+/// Apparent ecliptic longitude of `body_a` minus that of `body_b`
+/// (degrees, 0-360)
+pub fn longitude_difference(
+    eph: &Ephemeris,
+    body_a: &str,
+    body_b: &str,
+    jd: JulianDate,
+) -> Result<f64> {
+    let a = apparent::ecliptic_of_date(eph, body_a, jd)?;
+    let b = apparent::ecliptic_of_date(eph, body_b, jd)?;
+    Ok((a.longitude - b.longitude).rem_euclid(360.0))
+}
+
+/// Find the times at which the longitude of `body_a` minus that of
+/// `body_b` equals `difference`
+///
+/// # Arguments
+/// * `eph` - Loaded ephemeris
+/// * `body_a` - First body
+/// * `body_b` - Second body
+/// * `difference` - Target longitude difference (degrees)
+/// * `start` - Start of the search window (TDB)
+/// * `end` - End of the search window (TDB)
+pub fn find_longitude_difference(
+    eph: &Ephemeris,
+    body_a: &str,
+    body_b: &str,
+    difference: f64,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<Alignment>> {
+    let residual = |jd: JulianDate| -> Result<f64> {
+        let current = longitude_difference(eph, body_a, body_b, jd)?;
+        Ok((current - difference + 180.0).rem_euclid(360.0) - 180.0)
+    };
+
+    let mut events = Vec::new();
+    for root in finder(eph).roots(residual, start, end)? {
+        if residual(root.jd)?.abs() > WRAP_THRESHOLD {
+            continue;
+        }
+        let jd = root.jd;
+        events.push(Alignment {
+            jd,
+            separation: separation(eph, body_a, body_b, jd)?,
+        });
+    }
+    Ok(events)
+}
+
+/// Find the local minima and maxima of the angular separation of two
+/// bodies
+pub fn find_separation_extrema(
+    eph: &Ephemeris,
+    body_a: &str,
+    body_b: &str,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<SeparationExtremum>> {
+    let extrema = finder(eph).extrema(|jd| separation(eph, body_a, body_b, jd), start, end)?;
+
+    Ok(extrema
+        .into_iter()
+        .map(|extremum| SeparationExtremum {
+            jd: extremum.jd,
+            separation: extremum.value,
+            maximum: extremum.kind == EventKind::Maximum,
+        })
+        .collect())
+}
+
+/// Find the conjunctions in longitude of two bodies
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::conjunctions::find_conjunctions;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(2020, 12, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2021, 1, 1, 0, 0, 0.0)?;
+/// for event in find_conjunctions(&eph, "Jupiter", "Saturn", start, end)? {
+///     println!("JD {:.5}: {:.3} degrees apart", event.jd.as_f64(), event.separation);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_conjunctions(
+    eph: &Ephemeris,
+    body_a: &str,
+    body_b: &str,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<Alignment>> {
+    find_longitude_difference(eph, body_a, body_b, 0.0, start, end)
+}
+
+/// Find the oppositions of a body to the Sun
+pub fn find_oppositions(
+    eph: &Ephemeris,
+    body_name: &str,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<Alignment>> {
+    find_longitude_difference(eph, body_name, "Sun", 180.0, start, end)
+}
+
+/// Find the greatest elongations of a body from the Sun, typically Mercury
+/// or Venus
+pub fn find_greatest_elongations(
+    eph: &Ephemeris,
+    body_name: &str,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<Elongation>> {
+    let mut events = Vec::new();
+    for extremum in find_separation_extrema(eph, body_name, "Sun", start, end)? {
+        if !extremum.maximum {
+            continue;
+        }
+        let side = if longitude_difference(eph, body_name, "Sun", extremum.jd)? < 180.0 {
+            ElongationSide::East
+        } else {
+            ElongationSide::West
+        };
+        events.push(Elongation {
+            jd: extremum.jd,
+            elongation: extremum.separation,
+            side,
+        });
+    }
+    Ok(events)
+}
+
+fn finder(eph: &Ephemeris) -> EventFinder {
+    EventFinder::for_ephemeris(eph)
+        .with_step(SEARCH_STEP)
+        .with_tolerance(TOLERANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::{model, MODEL_EMB, MODEL_VENUS};
+
+    /// Time after `jd` at which the heliocentric longitude of the model
+    /// Venus leads that of the Earth by `lead` degrees
+    fn venus_lead(jd: f64, lead: f64) -> f64 {
+        let rate = 360.0 / MODEL_VENUS.period - 360.0 / MODEL_EMB.period;
+        let current = MODEL_VENUS.m0 - MODEL_EMB.m0 + rate * (jd - 2451545.0);
+        jd + (lead - current).rem_euclid(360.0) / rate
+    }
+
+    #[test]
+    fn test_find_longitude_difference() {
+        let eph = model();
+        let (start, end) = (JulianDate::new(2451540.0), JulianDate::new(2452330.0));
+
+        // Superior, inferior and superior conjunctions, Venus in the ecliptic
+        let conjunctions =
+            find_longitude_difference(&eph, "Venus", "Sun", 0.0, start, end).unwrap();
+        assert_eq!(conjunctions.len(), 3);
+        let mut expected = start.jd;
+        for (event, lead) in conjunctions.iter().zip([180.0, 0.0, 180.0]) {
+            expected = venus_lead(expected, lead);
+            assert!((event.jd.jd - expected).abs() < 0.01);
+            assert!(event.separation < 1e-3);
+            let residual = longitude_difference(&eph, "Venus", "Sun", event.jd).unwrap();
+            assert!(residual.min(360.0 - residual) < 1e-5);
+        }
+
+        // Mars passes both 180 and, at conjunction, the wrap of the residual
+        let oppositions = find_oppositions(&eph, "Mars", start, end).unwrap();
+        assert_eq!(oppositions.len(), 1);
+        assert!((oppositions[0].separation - 180.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_find_greatest_elongations() {
+        let eph = model();
+        let (start, end) = (JulianDate::new(2451540.0), JulianDate::new(2452330.0));
+        let elongations = find_greatest_elongations(&eph, "Venus", start, end).unwrap();
+        let sides: Vec<ElongationSide> = elongations.iter().map(|e| e.side).collect();
+        assert_eq!(sides, [ElongationSide::East, ElongationSide::West]);
+
+        // For circular orbits the Sun-Venus-Earth angle is then 90 degrees
+        let radius_ratio = MODEL_VENUS.a / MODEL_EMB.a;
+        let angle = radius_ratio.acos().to_degrees();
+        for (event, lead) in elongations.iter().zip([-angle, angle]) {
+            assert!((event.elongation - radius_ratio.asin().to_degrees()).abs() < 0.01);
+            assert!((event.jd.jd - venus_lead(start.jd, lead)).abs() < 0.5);
+        }
+    }
+}
@@ -0,0 +1,708 @@
+//! Solar and lunar eclipses: global classification, magnitude and gamma,
+//! and local circumstances of solar eclipses for an observer
+//!
+//! The Earth, Moon and Sun radii come from the ephemeris header (RE, AM,
+//! ASUN). The Earth is treated as a sphere of equatorial radius, so
+//! eclipses grazing the polar regions may be misclassified. Lunar shadow
+//! radii follow Danjon's rule, enlarging the Earth's parallax by 1% for
+//! the atmosphere.
+
+use crate::apparent::{self, ASTRONOMICAL_UNIT};
+use crate::ephemeris::{Ephemeris, Position};
+use crate::lunar::{self, MoonPhase};
+use crate::observer::Observer;
+use crate::search;
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Half-width of the window searched around each syzygy and around
+/// greatest eclipse (days)
+const ECLIPSE_WINDOW: f64 = 0.25;
+
+/// Precision of the returned times (days, about 0.01 s)
+const ECLIPSE_TOLERANCE: f64 = 1e-7;
+
+/// Enlargement of the Earth's shadow by the atmosphere (Danjon)
+const SHADOW_ENLARGEMENT: f64 = 1.01;
+
+/// Type of solar eclipse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolarEclipseKind {
+    /// Only the penumbra reaches the Earth
+    Partial,
+    /// The antumbra reaches the Earth: the Moon appears smaller than the Sun
+    Annular,
+    /// The umbra reaches the Earth
+    Total,
+    /// Total near greatest eclipse and annular at the ends of the path
+    Hybrid,
+}
+
+/// Type of lunar eclipse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LunarEclipseKind {
+    /// The Moon enters only the penumbra
+    Penumbral,
+    /// Part of the Moon enters the umbra
+    Partial,
+    /// The whole Moon enters the umbra
+    Total,
+}
+
+/// Global circumstances of a solar eclipse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarEclipse {
+    /// Type of eclipse
+    pub kind: SolarEclipseKind,
+    /// Time of greatest eclipse, when the shadow axis is closest to the
+    /// Earth's centre (TDB)
+    pub greatest: JulianDate,
+    /// Fraction of the Sun's diameter covered at greatest eclipse; for
+    /// central eclipses the Moon/Sun diameter ratio on the central line
+    pub magnitude: f64,
+    /// Distance of the shadow axis from the Earth's centre in Earth radii,
+    /// positive north of the equator
+    pub gamma: f64,
+}
+
+/// Global circumstances of a lunar eclipse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LunarEclipse {
+    /// Type of eclipse
+    pub kind: LunarEclipseKind,
+    /// Time of greatest eclipse, when the Moon is closest to the shadow
+    /// axis (TDB)
+    pub greatest: JulianDate,
+    /// Fraction of the Moon's diameter inside the umbra at greatest eclipse
+    pub umbral_magnitude: f64,
+    /// Fraction of the Moon's diameter inside the penumbra at greatest eclipse
+    pub penumbral_magnitude: f64,
+    /// Distance of the Moon's centre from the shadow axis in Earth radii,
+    /// positive north of the axis
+    pub gamma: f64,
+    /// Start and end of the penumbral phase (TDB)
+    pub penumbral: (JulianDate, JulianDate),
+    /// Start and end of the partial umbral phase (TDB)
+    pub partial: Option<(JulianDate, JulianDate)>,
+    /// Start and end of totality (TDB)
+    pub total: Option<(JulianDate, JulianDate)>,
+}
+
+/// Solar eclipse as seen by an observer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalSolarEclipse {
+    /// Type of eclipse at the observer
+    pub kind: SolarEclipseKind,
+    /// Time of maximum eclipse at the observer (TDB)
+    pub maximum: JulianDate,
+    /// Fraction of the Sun's diameter covered at maximum
+    pub magnitude: f64,
+    /// Fraction of the Sun's disk area covered at maximum
+    pub obscuration: f64,
+    /// Airless altitude of the Sun at maximum (degrees); negative values
+    /// mean the eclipse is not visible at that moment
+    pub sun_altitude: f64,
+    /// First and last contacts (TDB)
+    pub partial: (JulianDate, JulianDate),
+    /// Second and third contacts, for total or annular eclipses (TDB)
+    pub central: Option<(JulianDate, JulianDate)>,
+}
+
+/// Shadow cone of the Moon on the fundamental plane through the Earth's
+/// centre perpendicular to the shadow axis, in Earth radii
+struct SolarGeometry {
+    gamma: f64,
+    penumbra: f64,
+    umbra: f64,
+    tan_penumbra: f64,
+    tan_umbra: f64,
+}
+
+/// Angular quantities of the Moon and the Earth's shadow seen from the
+/// Earth's centre (radians)
+struct LunarGeometry {
+    separation: f64,
+    umbra: f64,
+    penumbra: f64,
+    moon_radius: f64,
+    gamma: f64,
+}
+
+/// Find the solar eclipses between two dates
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::eclipse::find_solar_eclipses;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2025, 1, 1, 0, 0, 0.0)?;
+/// for eclipse in find_solar_eclipses(&eph, start, end)? {
+///     println!("{:?} eclipse, gamma {:.4}", eclipse.kind, eclipse.gamma);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_solar_eclipses(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<SolarEclipse>> {
+    let mut eclipses = Vec::new();
+    for syzygy in syzygies(eph, MoonPhase::NewMoon, start, end)? {
+        let (jd, _) = search::minimize(
+            |jd| Ok(solar_geometry(eph, JulianDate::new(jd))?.gamma.abs()),
+            syzygy - ECLIPSE_WINDOW,
+            syzygy + ECLIPSE_WINDOW,
+            ECLIPSE_TOLERANCE,
+        )?;
+        let geometry = solar_geometry(eph, JulianDate::new(jd))?;
+        if let Some((kind, magnitude)) = classify_solar(&geometry) {
+            eclipses.push(SolarEclipse {
+                kind,
+                greatest: JulianDate::new(jd),
+                magnitude,
+                gamma: geometry.gamma,
+            });
+        }
+    }
+    Ok(eclipses)
+}
+
+/// Find the lunar eclipses between two dates
+pub fn find_lunar_eclipses(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<LunarEclipse>> {
+    let mut eclipses = Vec::new();
+    for syzygy in syzygies(eph, MoonPhase::FullMoon, start, end)? {
+        let (jd, _) = search::minimize(
+            |jd| Ok(lunar_geometry(eph, JulianDate::new(jd))?.separation),
+            syzygy - ECLIPSE_WINDOW,
+            syzygy + ECLIPSE_WINDOW,
+            ECLIPSE_TOLERANCE,
+        )?;
+        let geometry = lunar_geometry(eph, JulianDate::new(jd))?;
+        let magnitude = |radius: f64| {
+            (radius + geometry.moon_radius - geometry.separation) / (2.0 * geometry.moon_radius)
+        };
+        let umbral_magnitude = magnitude(geometry.umbra);
+        let penumbral_magnitude = magnitude(geometry.penumbra);
+        if penumbral_magnitude <= 0.0 {
+            continue;
+        }
+
+        // Contacts: the Moon's centre at a given angular distance from the
+        // shadow axis, before and after greatest eclipse
+        let contacts = |radius: fn(&LunarGeometry) -> f64| {
+            contact_pair(jd, |t| {
+                let g = lunar_geometry(eph, JulianDate::new(t))?;
+                Ok(g.separation - radius(&g))
+            })
+        };
+        let penumbral = contacts(|g| g.penumbra + g.moon_radius)?
+            .ok_or_else(|| Error::Ephemeris("Penumbral contacts not found".to_string()))?;
+        let partial = contacts(|g| g.umbra + g.moon_radius)?;
+        let total = contacts(|g| g.umbra - g.moon_radius)?;
+
+        let kind = if total.is_some() {
+            LunarEclipseKind::Total
+        } else if partial.is_some() {
+            LunarEclipseKind::Partial
+        } else {
+            LunarEclipseKind::Penumbral
+        };
+
+        eclipses.push(LunarEclipse {
+            kind,
+            greatest: JulianDate::new(jd),
+            umbral_magnitude,
+            penumbral_magnitude,
+            gamma: geometry.gamma,
+            penumbral,
+            partial,
+            total,
+        });
+    }
+    Ok(eclipses)
+}
+
+/// Local circumstances of a solar eclipse for an observer
+///
+/// Contacts are computed from the topocentric apparent disks of the Sun and
+/// Moon regardless of whether the Sun is above the horizon.
+///
+/// # Returns
+/// `None` if the Moon's disk does not overlap the Sun's at the observer
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::eclipse::{find_solar_eclipses, local_solar_eclipse};
+/// use rust_jpl::{Ephemeris, JulianDate, Observer};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let dallas = Observer::new(32.78, -96.80, 140.0)?;
+/// let start = JulianDate::from_calendar(2024, 4, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2024, 5, 1, 0, 0, 0.0)?;
+/// for eclipse in find_solar_eclipses(&eph, start, end)? {
+///     if let Some(local) = local_solar_eclipse(&eph, &dallas, &eclipse)? {
+///         println!("{:?}, magnitude {:.3}", local.kind, local.magnitude);
+///     }
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn local_solar_eclipse(
+    eph: &Ephemeris,
+    observer: &Observer,
+    eclipse: &SolarEclipse,
+) -> Result<Option<LocalSolarEclipse>> {
+    let au = eph.get_constant("AU").unwrap_or(ASTRONOMICAL_UNIT);
+    let sun_radius = radius(eph, "Sun")? / au;
+    let moon_radius = radius(eph, "Moon")? / au;
+
+    // Topocentric angular separation and semi-diameters (radians)
+    let disks = |jd: f64| -> Result<(f64, f64, f64)> {
+        let jd = JulianDate::new(jd);
+        let sun = observer.apparent(eph, "Sun", jd)?;
+        let moon = observer.apparent(eph, "Moon", jd)?;
+        let separation = sun.unit().dot(&moon.unit()).clamp(-1.0, 1.0).acos();
+        Ok((
+            separation,
+            (sun_radius / sun.distance()).asin(),
+            (moon_radius / moon.distance()).asin(),
+        ))
+    };
+
+    let (maximum, _) = search::minimize(
+        |jd| Ok(disks(jd)?.0),
+        eclipse.greatest.jd - ECLIPSE_WINDOW,
+        eclipse.greatest.jd + ECLIPSE_WINDOW,
+        ECLIPSE_TOLERANCE,
+    )?;
+    let (separation, rs, rm) = disks(maximum)?;
+    if separation >= rs + rm {
+        return Ok(None);
+    }
+
+    let partial = contact_pair(maximum, |jd| {
+        let (d, rs, rm) = disks(jd)?;
+        Ok(d - (rs + rm))
+    })?;
+    let central = contact_pair(maximum, |jd| {
+        let (d, rs, rm) = disks(jd)?;
+        Ok(d - (rs - rm).abs())
+    })?;
+    let partial = match partial {
+        Some(partial) => partial,
+        None => return Ok(None),
+    };
+
+    let kind = match central {
+        Some(_) if rm > rs => SolarEclipseKind::Total,
+        Some(_) => SolarEclipseKind::Annular,
+        None => SolarEclipseKind::Partial,
+    };
+    let maximum = JulianDate::new(maximum);
+
+    Ok(Some(LocalSolarEclipse {
+        kind,
+        maximum,
+        magnitude: (rs + rm - separation) / (2.0 * rs),
+        obscuration: obscuration(separation, rs, rm),
+        sun_altitude: observer.horizontal(eph, "Sun", maximum, None)?.altitude,
+        partial,
+        central,
+    }))
+}
+
+/// Times of the new or full moons in a window (TDB)
+fn syzygies(
+    eph: &Ephemeris,
+    phase: MoonPhase,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<f64>> {
+    Ok(lunar::find_phases(eph, start, end)?
+        .into_iter()
+        .filter(|event| event.phase == phase)
+        .map(|event| event.jd.jd)
+        .collect())
+}
+
+/// Roots of `f` before and after `centre`, where `f` is negative
+///
+/// Returns `None` if `f(centre)` is not negative or a root lies outside the
+/// search window.
+fn contact_pair<F>(centre: f64, mut f: F) -> Result<Option<(JulianDate, JulianDate)>>
+where
+    F: FnMut(f64) -> Result<f64>,
+{
+    let at_centre = f(centre)?;
+    let before = centre - ECLIPSE_WINDOW;
+    let after = centre + ECLIPSE_WINDOW;
+    if at_centre >= 0.0 || f(before)? < 0.0 || f(after)? < 0.0 {
+        return Ok(None);
+    }
+    let first = search::bisect(&mut f, before, centre, 1.0, ECLIPSE_TOLERANCE)?;
+    let last = search::bisect(&mut f, centre, after, at_centre, ECLIPSE_TOLERANCE)?;
+    Ok(Some((JulianDate::new(first), JulianDate::new(last))))
+}
+
+/// Radius of a body from the ephemeris (km)
+fn radius(eph: &Ephemeris, body_name: &str) -> Result<f64> {
+    eph.get_radius(body_name)
+        .ok_or_else(|| Error::Ephemeris(format!("No radius available for {}", body_name)))
+}
+
+/// Position of a body relative to the Earth's centre, in Earth radii
+fn geocentric(eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<Position> {
+    let au = eph.get_constant("AU").unwrap_or(ASTRONOMICAL_UNIT);
+    Ok(apparent::astrometric(eph, body_name, jd)? * (au / radius(eph, "Earth")?))
+}
+
+fn solar_geometry(eph: &Ephemeris, jd: JulianDate) -> Result<SolarGeometry> {
+    let earth_radius = radius(eph, "Earth")?;
+    let sun_radius = radius(eph, "Sun")? / earth_radius;
+    let moon_radius = radius(eph, "Moon")? / earth_radius;
+    let sun = geocentric(eph, "Sun", jd)?;
+    let moon = geocentric(eph, "Moon", jd)?;
+
+    // Shadow axis from the Sun through the Moon, and its closest approach
+    // to the Earth's centre
+    let axis = (moon - sun).unit();
+    let closest = moon - axis * moon.dot(&axis);
+    let moon_height = -moon.dot(&axis);
+
+    let sun_moon = (moon - sun).distance();
+    let f1 = ((sun_radius + moon_radius) / sun_moon).asin();
+    let f2 = ((sun_radius - moon_radius) / sun_moon).asin();
+
+    Ok(SolarGeometry {
+        gamma: closest.distance().copysign(closest.z),
+        penumbra: moon_height * f1.tan() + moon_radius / f1.cos(),
+        umbra: moon_height * f2.tan() - moon_radius / f2.cos(),
+        tan_penumbra: f1.tan(),
+        tan_umbra: f2.tan(),
+    })
+}
+
+/// Type and magnitude of a solar eclipse from the geometry at greatest
+/// eclipse, or `None` if the penumbra misses the Earth
+fn classify_solar(g: &SolarGeometry) -> Option<(SolarEclipseKind, f64)> {
+    let distance = g.gamma.abs();
+    if distance >= 1.0 + g.penumbra {
+        return None;
+    }
+
+    if distance >= 1.0 {
+        // Non-central: only the edge of the shadow touches the Earth
+        let kind = if distance >= 1.0 + g.umbra.abs() {
+            SolarEclipseKind::Partial
+        } else if g.umbra < 0.0 {
+            SolarEclipseKind::Total
+        } else {
+            SolarEclipseKind::Annular
+        };
+        let magnitude = (g.penumbra - (distance - 1.0)) / (g.penumbra + g.umbra);
+        return Some((kind, magnitude));
+    }
+
+    // Central: shadow radii on the Earth's surface below the axis point
+    let height = (1.0 - distance * distance).sqrt();
+    let penumbra = g.penumbra - height * g.tan_penumbra;
+    let umbra = g.umbra - height * g.tan_umbra;
+    let kind = if g.umbra < 0.0 {
+        SolarEclipseKind::Total
+    } else if umbra < 0.0 {
+        SolarEclipseKind::Hybrid
+    } else {
+        SolarEclipseKind::Annular
+    };
+    Some((kind, (penumbra - umbra) / (penumbra + umbra)))
+}
+
+fn lunar_geometry(eph: &Ephemeris, jd: JulianDate) -> Result<LunarGeometry> {
+    let sun = geocentric(eph, "Sun", jd)?;
+    let moon = geocentric(eph, "Moon", jd)?;
+    let earth_radius = radius(eph, "Earth")?;
+    let sun_radius = radius(eph, "Sun")? / earth_radius;
+    let moon_radius = radius(eph, "Moon")? / earth_radius;
+
+    let axis = -sun.unit();
+    let offset = moon - axis * moon.dot(&axis);
+
+    let moon_parallax = (1.0 / moon.distance()).asin();
+    let sun_parallax = (1.0 / sun.distance()).asin();
+    let sun_semidiameter = (sun_radius / sun.distance()).asin();
+    let shadow = SHADOW_ENLARGEMENT * moon_parallax + sun_parallax;
+
+    Ok(LunarGeometry {
+        separation: moon.unit().dot(&axis).clamp(-1.0, 1.0).acos(),
+        umbra: shadow - sun_semidiameter,
+        penumbra: shadow + sun_semidiameter,
+        moon_radius: (moon_radius / moon.distance()).asin(),
+        gamma: offset.distance().copysign(offset.z),
+    })
+}
+
+/// Fraction of the area of a disk of radius `rs` covered by a disk of
+/// radius `rm` whose centre is `d` away
+fn obscuration(d: f64, rs: f64, rm: f64) -> f64 {
+    if d >= rs + rm {
+        return 0.0;
+    }
+    if d <= (rs - rm).abs() {
+        return (rm.min(rs) / rs).powi(2);
+    }
+    let a = ((d * d + rs * rs - rm * rm) / (2.0 * d * rs))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let b = ((d * d + rm * rm - rs * rs) / (2.0 * d * rm))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let lens = rs * rs * a + rm * rm * b
+        - 0.5 * ((-d + rs + rm) * (d + rs - rm) * (d - rs + rm) * (d + rs + rm)).sqrt();
+    lens / (std::f64::consts::PI * rs * rs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::model;
+
+    /// Shadow radii on the fundamental plane from Besselian elements
+    fn besselian(gamma: f64, l1: f64, l2: f64, tan_f1: f64, tan_f2: f64) -> SolarGeometry {
+        SolarGeometry {
+            gamma,
+            penumbra: l1,
+            umbra: l2,
+            tan_penumbra: tan_f1,
+            tan_umbra: tan_f2,
+        }
+    }
+
+    #[test]
+    fn test_classify_solar() {
+        // 2024 April 8, total: gamma 0.3431, magnitude 1.0566
+        let total = besselian(0.3431, 0.535814, -0.010272, 0.0046683, 0.0046450);
+        let (kind, magnitude) = classify_solar(&total).unwrap();
+        assert_eq!(kind, SolarEclipseKind::Total);
+        assert!((magnitude - 1.0566).abs() < 1e-4);
+
+        // 2023 October 14, annular: gamma 0.3753, magnitude 0.9520
+        let annular = besselian(0.3753, 0.5642, 0.0180, 0.004688, 0.004665);
+        let (kind, magnitude) = classify_solar(&annular).unwrap();
+        assert_eq!(kind, SolarEclipseKind::Annular);
+        assert!((magnitude - 0.9520).abs() < 1e-3);
+
+        // 2025 March 29, partial: gamma 1.0405, magnitude 0.9376, with
+        // typical shadow radii for a Moon near perigee, hence the looser
+        // tolerance
+        let partial = besselian(1.0405, 0.5366, -0.0100, 0.004740, 0.004716);
+        let (kind, magnitude) = classify_solar(&partial).unwrap();
+        assert_eq!(kind, SolarEclipseKind::Partial);
+        assert!((magnitude - 0.9376).abs() < 0.01);
+
+        // A non-central umbra grazing the Earth, and a penumbra missing it
+        let grazing = besselian(1.005, 0.5366, -0.0100, 0.004740, 0.004716);
+        assert_eq!(classify_solar(&grazing).unwrap().0, SolarEclipseKind::Total);
+        let miss = besselian(-1.6, 0.5366, -0.0100, 0.004740, 0.004716);
+        assert_eq!(classify_solar(&miss), None);
+    }
+
+    /// Window of the model ephemeris searched by the tests
+    fn window() -> (JulianDate, JulianDate) {
+        (JulianDate::new(2451540.0), JulianDate::new(2452330.0))
+    }
+
+    /// Syzygies of a phase with the Moon's distance from the Sun-Earth line
+    /// in Earth radii estimated from its ecliptic latitude
+    fn syzygy_offsets(eph: &Ephemeris, phase: MoonPhase) -> Vec<(f64, f64)> {
+        let (start, end) = window();
+        syzygies(eph, phase, start, end)
+            .unwrap()
+            .into_iter()
+            .map(|jd| {
+                let jd = JulianDate::new(jd);
+                let moon = apparent::ecliptic_of_date(eph, "Moon", jd).unwrap();
+                let distance = geocentric(eph, "Moon", jd).unwrap().distance();
+                (jd.jd, moon.latitude.to_radians() * distance)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_solar_eclipses() {
+        let eph = model();
+        let (start, end) = window();
+        let eclipses = find_solar_eclipses(&eph, start, end).unwrap();
+        let kinds: Vec<_> = eclipses.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SolarEclipseKind::Annular,
+                SolarEclipseKind::Total,
+                SolarEclipseKind::Annular,
+                SolarEclipseKind::Total,
+                SolarEclipseKind::Partial,
+            ]
+        );
+
+        // New moons near a node give an eclipse, those far from one none
+        for (jd, offset) in syzygy_offsets(&eph, MoonPhase::NewMoon) {
+            let found = eclipses.iter().find(|e| (e.greatest.jd - jd).abs() < 0.1);
+            if offset.abs() < 1.3 {
+                let eclipse = found.unwrap();
+                assert!((eclipse.gamma - offset).abs() < 0.15);
+            } else if offset.abs() > 1.6 {
+                assert!(found.is_none());
+            }
+        }
+        assert!(
+            find_solar_eclipses(&eph, JulianDate::new(2451630.0), JulianDate::new(2451650.0))
+                .unwrap()
+                .is_empty()
+        );
+
+        // Central eclipses are total when the Moon, seen from the point under
+        // the shadow axis, is larger than the Sun
+        let earth_radius = radius(&eph, "Earth").unwrap();
+        for eclipse in eclipses.iter().filter(|e| e.gamma.abs() < 1.0) {
+            let height = (1.0 - eclipse.gamma.powi(2)).sqrt();
+            let moon = geocentric(&eph, "Moon", eclipse.greatest)
+                .unwrap()
+                .distance()
+                - height;
+            let sun = geocentric(&eph, "Sun", eclipse.greatest)
+                .unwrap()
+                .distance()
+                - height;
+            let ratio = (radius(&eph, "Moon").unwrap() / earth_radius / moon)
+                / (radius(&eph, "Sun").unwrap() / earth_radius / sun);
+            assert_eq!(eclipse.kind == SolarEclipseKind::Total, ratio > 1.0);
+            assert!((eclipse.magnitude - ratio).abs() < 2e-3);
+        }
+        let partial = eclipses[4];
+        assert!(partial.gamma.abs() > 1.0);
+        assert!(partial.magnitude > 0.0 && partial.magnitude < 1.0);
+    }
+
+    #[test]
+    fn test_find_lunar_eclipses() {
+        let eph = model();
+        let (start, end) = window();
+        let eclipses = find_lunar_eclipses(&eph, start, end).unwrap();
+        assert_eq!(eclipses.len(), 6);
+
+        for (jd, offset) in syzygy_offsets(&eph, MoonPhase::FullMoon) {
+            let found = eclipses.iter().find(|e| (e.greatest.jd - jd).abs() < 0.1);
+            if offset.abs() < 1.3 {
+                let eclipse = found.unwrap();
+                assert!((eclipse.gamma - offset).abs() < 0.1);
+                // The Moon's centre passes within about 0.3 Earth radii of
+                // the axis only in total eclipses
+                let total = offset.abs() < 0.3;
+                assert_eq!(eclipse.kind == LunarEclipseKind::Total, total);
+            } else if offset.abs() > 1.7 {
+                assert!(found.is_none());
+            }
+        }
+
+        for eclipse in &eclipses {
+            let (begin, finish) = eclipse.penumbral;
+            assert!(begin.jd < eclipse.greatest.jd && eclipse.greatest.jd < finish.jd);
+            assert!(eclipse.penumbral_magnitude > eclipse.umbral_magnitude);
+            if let Some((begin, finish)) = eclipse.partial {
+                assert!(eclipse.penumbral.0.jd < begin.jd && finish.jd < eclipse.penumbral.1.jd);
+            }
+            if let Some((begin, finish)) = eclipse.total {
+                let partial = eclipse.partial.unwrap();
+                assert!(partial.0.jd < begin.jd && finish.jd < partial.1.jd);
+                assert!(eclipse.umbral_magnitude > 1.0);
+            }
+        }
+    }
+
+    /// Observer on the ellipsoid below the point where the shadow axis
+    /// meets a spherical Earth at greatest eclipse
+    fn central_observer(eph: &Ephemeris, eclipse: &SolarEclipse) -> Observer {
+        let jd = eclipse.greatest;
+        let sun = geocentric(eph, "Sun", jd).unwrap();
+        let moon = geocentric(eph, "Moon", jd).unwrap();
+        let axis = (moon - sun).unit();
+        let closest = moon - axis * moon.dot(&axis);
+        let surface = closest - axis * (1.0 - eclipse.gamma.powi(2)).sqrt();
+
+        let site = Observer::new(0.0, 0.0, 0.0).unwrap();
+        let terrestrial = site
+            .celestial_to_terrestrial(eph, jd)
+            .unwrap()
+            .apply(&surface);
+        let f = site.ellipsoid.flattening();
+        let geocentric_latitude = (terrestrial.z / terrestrial.distance()).asin();
+        let latitude = (geocentric_latitude.tan() / (1.0 - f).powi(2)).atan();
+        let longitude = terrestrial.y.atan2(terrestrial.x);
+        let ellipsoid = Observer::new(latitude.to_degrees(), longitude.to_degrees(), 0.0)
+            .unwrap()
+            .terrestrial_position()
+            .distance();
+        let height = radius(eph, "Earth").unwrap() - ellipsoid;
+        Observer::new(
+            latitude.to_degrees(),
+            longitude.to_degrees(),
+            height * 1000.0,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_local_solar_eclipse() {
+        let eph = model();
+        let (start, end) = window();
+        let eclipses = find_solar_eclipses(&eph, start, end).unwrap();
+        let total = eclipses[1];
+
+        let central = central_observer(&eph, &total);
+        let local = local_solar_eclipse(&eph, &central, &total)
+            .unwrap()
+            .unwrap();
+        assert_eq!(local.kind, SolarEclipseKind::Total);
+        let (second, third) = local.central.unwrap();
+        assert!(local.partial.0.jd < second.jd);
+        assert!(second.jd < local.maximum.jd && local.maximum.jd < third.jd);
+        assert!(third.jd < local.partial.1.jd);
+        assert!((local.maximum.jd - total.greatest.jd).abs() < 0.01);
+        assert!(local.magnitude > 1.0);
+        assert_eq!(local.obscuration, 1.0);
+        assert!(local.sun_altitude > 0.0);
+
+        // Twenty degrees north of the central line only part of the Sun is
+        // covered
+        let north = Observer::new(central.latitude + 20.0, central.longitude, 0.0).unwrap();
+        let local = local_solar_eclipse(&eph, &north, &total).unwrap().unwrap();
+        assert_eq!(local.kind, SolarEclipseKind::Partial);
+        assert!(local.central.is_none());
+        assert!(local.partial.0.jd < local.maximum.jd && local.maximum.jd < local.partial.1.jd);
+        assert!(local.obscuration > 0.0 && local.obscuration < 1.0);
+        assert!(local.magnitude > 0.0 && local.magnitude < 1.0);
+
+        // On the other side of the Earth the disks do not meet
+        let antipode = Observer::new(
+            -central.latitude,
+            (central.longitude + 180.0).rem_euclid(360.0),
+            0.0,
+        )
+        .unwrap();
+        assert!(local_solar_eclipse(&eph, &antipode, &total)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_obscuration() {
+        assert_eq!(obscuration(1.0, 0.25, 0.25), 0.0);
+        assert!((obscuration(0.0, 0.25, 0.3) - 1.0).abs() < 1e-12);
+        assert!((obscuration(0.0, 0.25, 0.2) - 0.64).abs() < 1e-12);
+        // Equal disks offset by one radius cover about 39.1% of each other
+        assert!((obscuration(0.25, 0.25, 0.25) - 0.391002).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,453 @@
+//! Osculating Keplerian elements and conversion to and from state vectors
+//!
+//! Elements are referred to the ecliptic and equinox of J2000 and use the
+//! periapsis distance rather than the semi-major axis, so elliptic,
+//! parabolic and hyperbolic orbits share one representation. Angles are in
+//! degrees, distances in AU and gravitational parameters in AU^3/day^2.
+
+use std::f64::consts::{PI, TAU};
+
+use crate::ephemeris::{Ephemeris, Position, State};
+use crate::frames::{self, Matrix3};
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Eccentricities closer to 1 than this are treated as parabolic
+const PARABOLIC_TOLERANCE: f64 = 1e-10;
+
+/// Eccentricities and inclinations (radians) below this are treated as zero
+/// when choosing the node and periapsis of degenerate orbits
+const SINGULAR_TOLERANCE: f64 = 1e-12;
+
+/// Iterations allowed when solving Kepler's equation
+const KEPLER_ITERATIONS: usize = 100;
+
+/// Order of the Laguerre-Conway iteration for the universal anomaly
+const LAGUERRE_ORDER: f64 = 5.0;
+
+/// Shape of a conic orbit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitKind {
+    /// Eccentricity below 1
+    Elliptic,
+    /// Eccentricity of 1
+    Parabolic,
+    /// Eccentricity above 1
+    Hyperbolic,
+}
+
+/// Osculating Keplerian elements of a two-body orbit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitalElements {
+    /// Epoch of osculation (TDB)
+    pub epoch: JulianDate,
+    /// Gravitational parameter of the central and orbiting bodies (AU^3/day^2)
+    pub gm: f64,
+    /// Periapsis distance q (AU)
+    pub periapsis_distance: f64,
+    /// Eccentricity e
+    pub eccentricity: f64,
+    /// Inclination i (degrees)
+    pub inclination: f64,
+    /// Longitude of the ascending node Ω (degrees)
+    pub ascending_node: f64,
+    /// Argument of periapsis ω (degrees)
+    pub argument_of_periapsis: f64,
+    /// Mean anomaly M at the epoch (degrees); for parabolic orbits the
+    /// Barker anomaly tan(ν/2) + tan³(ν/2)/3 expressed in degrees
+    pub mean_anomaly: f64,
+}
+
+impl OrbitalElements {
+    /// Elements of the orbit passing through a state
+    ///
+    /// # Arguments
+    /// * `state` - Position and velocity relative to the central body, ICRF axes (AU, AU/day)
+    /// * `gm` - Gravitational parameter of the central and orbiting bodies (AU^3/day^2)
+    /// * `epoch` - Time of the state
+    pub fn from_state(state: &State, gm: f64, epoch: JulianDate) -> Result<Self> {
+        let to_ecliptic = ecliptic_j2000();
+        let r = to_ecliptic.apply(&state.position);
+        let v = to_ecliptic.apply(&state.velocity);
+        let radius = r.distance();
+        let momentum = r.cross(&v);
+        let h = momentum.distance();
+        if radius == 0.0 || h == 0.0 || gm <= 0.0 {
+            return Err(Error::Ephemeris(
+                "Cannot compute elements of a degenerate orbit".to_string(),
+            ));
+        }
+
+        let eccentricity_vector = v.cross(&momentum) * (1.0 / gm) - r.unit();
+        let e = eccentricity_vector.distance();
+        let p = h * h / gm;
+
+        let inclination = (momentum.z / h).clamp(-1.0, 1.0).acos();
+        let node = if inclination.sin().abs() < SINGULAR_TOLERANCE {
+            0.0
+        } else {
+            momentum.x.atan2(-momentum.y)
+        };
+
+        // Argument of latitude, measured in the orbit plane from the node
+        let (sin_node, cos_node) = node.sin_cos();
+        let x = r.x * cos_node + r.y * sin_node;
+        let y = (r.y * cos_node - r.x * sin_node) * inclination.cos() + r.z * inclination.sin();
+        let latitude = y.atan2(x);
+
+        let true_anomaly = if e < SINGULAR_TOLERANCE {
+            latitude
+        } else {
+            ((p / gm).sqrt() * r.dot(&v)).atan2(p - radius)
+        };
+
+        Ok(Self {
+            epoch,
+            gm,
+            periapsis_distance: p / (1.0 + e),
+            eccentricity: e,
+            inclination: inclination.to_degrees(),
+            ascending_node: node.to_degrees().rem_euclid(360.0),
+            argument_of_periapsis: (latitude - true_anomaly).to_degrees().rem_euclid(360.0),
+            mean_anomaly: mean_from_true(true_anomaly, e).to_degrees(),
+        })
+    }
+
+    /// State relative to the central body at the epoch, ICRF axes (AU, AU/day)
+    pub fn to_state(&self) -> State {
+        let e = self.eccentricity;
+        let nu = self.true_anomaly().to_radians();
+        let p = self.periapsis_distance * (1.0 + e);
+        let radius = p / (1.0 + e * nu.cos());
+        let speed = (self.gm / p).sqrt();
+
+        let position = Position::new(radius * nu.cos(), radius * nu.sin(), 0.0);
+        let velocity = Position::new(-speed * nu.sin(), speed * (e + nu.cos()), 0.0);
+
+        // Perifocal frame to the ecliptic, then to ICRF
+        let to_icrf = ecliptic_j2000().transpose().mul(
+            &frames::rotation_z(-self.ascending_node.to_radians())
+                .mul(&frames::rotation_x(-self.inclination.to_radians()))
+                .mul(&frames::rotation_z(
+                    -self.argument_of_periapsis.to_radians(),
+                )),
+        );
+        State::new(to_icrf.apply(&position), to_icrf.apply(&velocity))
+    }
+
+    /// State relative to the central body at another time, propagated on
+    /// the two-body orbit (ICRF axes, AU and AU/day)
+    pub fn state_at(&self, jd: JulianDate) -> Result<State> {
+        propagate(&self.to_state(), self.gm, jd.jd - self.epoch.jd)
+    }
+
+    /// Shape of the orbit
+    pub fn kind(&self) -> OrbitKind {
+        orbit_kind(self.eccentricity)
+    }
+
+    /// Semi-major axis a (AU): negative for hyperbolic orbits and infinite
+    /// for parabolic ones
+    pub fn semi_major_axis(&self) -> f64 {
+        match self.kind() {
+            OrbitKind::Parabolic => f64::INFINITY,
+            _ => self.periapsis_distance / (1.0 - self.eccentricity),
+        }
+    }
+
+    /// True anomaly ν at the epoch (degrees, -180 to 180)
+    pub fn true_anomaly(&self) -> f64 {
+        true_from_mean(self.mean_anomaly.to_radians(), self.eccentricity).to_degrees()
+    }
+
+    /// Mean motion (degrees/day); for parabolic orbits the rate of the
+    /// Barker anomaly
+    pub fn mean_motion(&self) -> f64 {
+        let q = self.periapsis_distance;
+        let n = match self.kind() {
+            OrbitKind::Parabolic => (self.gm / (2.0 * q * q * q)).sqrt(),
+            _ => (self.gm / self.semi_major_axis().abs().powi(3)).sqrt(),
+        };
+        n.to_degrees()
+    }
+
+    /// Orbital period (days), or `None` for open orbits
+    pub fn period(&self) -> Option<f64> {
+        match self.kind() {
+            OrbitKind::Elliptic => Some(360.0 / self.mean_motion()),
+            _ => None,
+        }
+    }
+}
+
+/// Gravitational parameter of a body from the ephemeris header (AU^3/day^2)
+///
+/// Uses GMS, GM1-GM9 and GMB, splitting GMB between the Earth and the Moon
+/// with EMRAT.
+pub fn gravitational_parameter(eph: &Ephemeris, body_name: &str) -> Option<f64> {
+    let name = body_name.to_ascii_lowercase().replace('_', "");
+    let emrat = eph.get_metadata().earth_moon_ratio;
+    match name.as_str() {
+        "sun" => eph.get_constant("GMS"),
+        "mercury" => eph.get_constant("GM1"),
+        "venus" => eph.get_constant("GM2"),
+        "earthmoonbarycenter" => eph.get_constant("GMB"),
+        "earth" => eph.get_constant("GMB").map(|gm| gm * emrat / (1.0 + emrat)),
+        "moon" | "moongeocentric" => eph.get_constant("GMB").map(|gm| gm / (1.0 + emrat)),
+        "mars" => eph.get_constant("GM4"),
+        "jupiter" => eph.get_constant("GM5"),
+        "saturn" => eph.get_constant("GM6"),
+        "uranus" => eph.get_constant("GM7"),
+        "neptune" => eph.get_constant("GM8"),
+        "pluto" => eph.get_constant("GM9"),
+        _ => None,
+    }
+}
+
+/// Osculating elements of a body relative to a central body
+///
+/// The gravitational parameter is the sum of both bodies' GMs; bodies
+/// without a GM in the header are treated as massless.
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::elements::osculating_elements;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let jd = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0)?;
+/// let mars = osculating_elements(&eph, "Mars", "Sun", jd)?;
+/// println!("a = {:.6} AU, e = {:.6}, i = {:.4}", mars.semi_major_axis(), mars.eccentricity, mars.inclination);
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn osculating_elements(
+    eph: &Ephemeris,
+    body_name: &str,
+    center_name: &str,
+    jd: JulianDate,
+) -> Result<OrbitalElements> {
+    let center_gm = gravitational_parameter(eph, center_name).ok_or_else(|| {
+        Error::Ephemeris(format!("No gravitational parameter for {}", center_name))
+    })?;
+    let gm = center_gm + gravitational_parameter(eph, body_name).unwrap_or(0.0);
+    let state = eph.get_state(body_name, jd)? - eph.get_state(center_name, jd)?;
+    OrbitalElements::from_state(&state, gm, jd)
+}
+
+/// Osculating heliocentric elements of a body
+pub fn heliocentric_elements(
+    eph: &Ephemeris,
+    body_name: &str,
+    jd: JulianDate,
+) -> Result<OrbitalElements> {
+    osculating_elements(eph, body_name, "Sun", jd)
+}
+
+/// Propagate a two-body state by `dt` days with universal variables
+///
+/// Works for elliptic, parabolic and hyperbolic orbits alike. The universal
+/// Kepler equation is solved with the Laguerre-Conway method.
+///
+/// # Arguments
+/// * `state` - Position and velocity relative to the central body (AU, AU/day)
+/// * `gm` - Gravitational parameter of the central and orbiting bodies (AU^3/day^2)
+/// * `dt` - Time step (days), positive or negative
+pub fn propagate(state: &State, gm: f64, dt: f64) -> Result<State> {
+    let r0 = state.position.distance();
+    if r0 == 0.0 || gm <= 0.0 {
+        return Err(Error::Ephemeris(
+            "Cannot propagate a degenerate orbit".to_string(),
+        ));
+    }
+    if dt == 0.0 {
+        return Ok(*state);
+    }
+
+    let sqrt_gm = gm.sqrt();
+    let v0 = state.velocity.distance();
+    let sigma0 = state.position.dot(&state.velocity) / sqrt_gm;
+    // Reciprocal of the semi-major axis: positive for ellipses
+    let alpha = 2.0 / r0 - v0 * v0 / gm;
+
+    // Universal Kepler equation F(x) = 0 and its first two derivatives
+    let equation = |x: f64| {
+        let z = alpha * x * x;
+        let (c, s) = stumpff(z);
+        let f = sigma0 * x * x * c + (1.0 - alpha * r0) * x * x * x * s + r0 * x - sqrt_gm * dt;
+        let df = sigma0 * x * (1.0 - z * s) + (1.0 - alpha * r0) * x * x * c + r0;
+        let ddf = sigma0 * (1.0 - z * c) + (1.0 - alpha * r0) * x * (1.0 - z * s);
+        (f, df, ddf)
+    };
+
+    let mut x = sqrt_gm * dt / r0;
+    let n = LAGUERRE_ORDER;
+    let mut converged = false;
+    for _ in 0..KEPLER_ITERATIONS {
+        let (f, df, ddf) = equation(x);
+        let root = ((n - 1.0) * (n - 1.0) * df * df - n * (n - 1.0) * f * ddf)
+            .abs()
+            .sqrt();
+        let step = n * f / (df + root.copysign(df));
+        x -= step;
+        if step.abs() <= 1e-14 * x.abs().max(1.0) {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        return Err(Error::Ephemeris(
+            "Kepler propagation did not converge".to_string(),
+        ));
+    }
+
+    // Lagrange coefficients
+    let (c, s) = stumpff(alpha * x * x);
+    let f = 1.0 - x * x / r0 * c;
+    let g = dt - x * x * x * s / sqrt_gm;
+    let position = state.position * f + state.velocity * g;
+    let r = position.distance();
+    let f_dot = sqrt_gm / (r * r0) * (alpha * x * x * x * s - x);
+    let g_dot = 1.0 - x * x / r * c;
+    Ok(State::new(
+        position,
+        state.position * f_dot + state.velocity * g_dot,
+    ))
+}
+
+/// Stumpff functions C(z) and S(z)
+fn stumpff(z: f64) -> (f64, f64) {
+    if z > 1e-6 {
+        let w = z.sqrt();
+        ((1.0 - w.cos()) / z, (w - w.sin()) / (w * z))
+    } else if z < -1e-6 {
+        let w = (-z).sqrt();
+        ((w.cosh() - 1.0) / -z, (w.sinh() - w) / (w * -z))
+    } else {
+        (
+            0.5 - z / 24.0 + z * z / 720.0,
+            1.0 / 6.0 - z / 120.0 + z * z / 5040.0,
+        )
+    }
+}
+
+/// Rotation from ICRF axes to the ecliptic and equinox of J2000
+fn ecliptic_j2000() -> Matrix3 {
+    frames::rotation_x(frames::mean_obliquity(frames::J2000))
+}
+
+fn orbit_kind(e: f64) -> OrbitKind {
+    if (e - 1.0).abs() < PARABOLIC_TOLERANCE {
+        OrbitKind::Parabolic
+    } else if e < 1.0 {
+        OrbitKind::Elliptic
+    } else {
+        OrbitKind::Hyperbolic
+    }
+}
+
+/// Mean anomaly from true anomaly (radians)
+pub(crate) fn mean_from_true(nu: f64, e: f64) -> f64 {
+    match orbit_kind(e) {
+        OrbitKind::Elliptic => {
+            let eccentric = ((1.0 - e * e).sqrt() * nu.sin()).atan2(e + nu.cos());
+            eccentric - e * eccentric.sin()
+        }
+        OrbitKind::Parabolic => {
+            let d = (nu / 2.0).tan();
+            d + d * d * d / 3.0
+        }
+        OrbitKind::Hyperbolic => {
+            let hyperbolic = 2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (nu / 2.0).tan()).atanh();
+            e * hyperbolic.sinh() - hyperbolic
+        }
+    }
+}
+
+/// True anomaly from mean anomaly (radians), solving Kepler's equation
+pub(crate) fn true_from_mean(mean: f64, e: f64) -> f64 {
+    match orbit_kind(e) {
+        OrbitKind::Elliptic => {
+            let m = (mean + PI).rem_euclid(TAU) - PI;
+            let mut eccentric = if e < 0.8 { m } else { PI.copysign(m) };
+            for _ in 0..KEPLER_ITERATIONS {
+                let step = (eccentric - e * eccentric.sin() - m) / (1.0 - e * eccentric.cos());
+                eccentric -= step;
+                if step.abs() < 1e-15 {
+                    break;
+                }
+            }
+            ((1.0 - e * e).sqrt() * eccentric.sin()).atan2(eccentric.cos() - e)
+        }
+        OrbitKind::Parabolic => {
+            // Closed-form solution of Barker's equation
+            let y = (1.5 * mean + (2.25 * mean * mean + 1.0).sqrt()).cbrt();
+            2.0 * (y - 1.0 / y).atan()
+        }
+        OrbitKind::Hyperbolic => {
+            let mut hyperbolic = (mean / e).asinh();
+            for _ in 0..KEPLER_ITERATIONS {
+                let step =
+                    (e * hyperbolic.sinh() - hyperbolic - mean) / (e * hyperbolic.cosh() - 1.0);
+                hyperbolic -= step;
+                if step.abs() < 1e-15 * hyperbolic.abs().max(1.0) {
+                    break;
+                }
+            }
+            2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (hyperbolic / 2.0).tanh()).atan()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Heliocentric gravitational parameter (AU^3/day^2)
+    const GM_SUN: f64 = 2.959122082855911e-4;
+
+    #[test]
+    fn test_state_round_trip() {
+        let epoch = JulianDate::new(frames::J2000);
+        let position = Position::new(1.2, -0.4, 0.3);
+        let speed = (GM_SUN / position.distance()).sqrt();
+        // Elliptic, parabolic and hyperbolic speeds along the same direction
+        for factor in [0.8, std::f64::consts::SQRT_2, 1.9] {
+            let velocity = Position::new(0.3, 0.9, 0.2).unit() * (speed * factor);
+            let state = State::new(position, velocity);
+            let elements = OrbitalElements::from_state(&state, GM_SUN, epoch).unwrap();
+            let back = elements.to_state();
+            assert!((back.position - position).distance() < 1e-12);
+            assert!((back.velocity - velocity).distance() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn test_propagation() {
+        // Universal-variable propagation agrees with advancing the mean anomaly
+        let epoch = JulianDate::new(frames::J2000);
+        let position = Position::new(1.2, -0.4, 0.3);
+        let speed = (GM_SUN / position.distance()).sqrt();
+        for factor in [0.8, std::f64::consts::SQRT_2, 1.9] {
+            let velocity = Position::new(0.3, 0.9, 0.2).unit() * (speed * factor);
+            let elements =
+                OrbitalElements::from_state(&State::new(position, velocity), GM_SUN, epoch)
+                    .unwrap();
+            for dt in [-400.0, 35.0, 900.0] {
+                let mut advanced = elements;
+                advanced.mean_anomaly += elements.mean_motion() * dt;
+                let expected = advanced.to_state();
+                let propagated = elements.state_at(JulianDate::new(epoch.jd + dt)).unwrap();
+                let scale = expected.position.distance();
+                assert!((propagated.position - expected.position).distance() < 1e-10 * scale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kepler_equation() {
+        // Meeus example 30.a: e = 0.1, M = 5 degrees gives E = 5.554589 degrees
+        let nu = true_from_mean(5f64.to_radians(), 0.1);
+        let eccentric = (0.99f64.sqrt() * nu.sin()).atan2(0.1 + nu.cos());
+        assert!((eccentric.to_degrees() - 5.554589).abs() < 1e-6);
+        assert!((mean_from_true(nu, 0.1).to_degrees() - 5.0).abs() < 1e-12);
+    }
+}
@@ -1,10 +1,14 @@
 //! Ephemeris data structures and position calculations
 
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::{Add, Mul, Neg, Sub};
 use std::str::FromStr;
+use std::sync::Mutex;
 
-use crate::config::AppConfig;
+use crate::ascii::AsciiHeader;
+use crate::builder::EphemerisBuilder;
+use crate::spk::{SpkKernel, SB441_N16};
 use crate::time::JulianDate;
 use crate::{Error, Result};
 
@@ -29,6 +33,108 @@ impl Position {
     pub fn distance(&self) -> f64 {
         (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
     }
+
+    /// Dot product with another vector
+    pub fn dot(&self, other: &Position) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Cross product with another vector
+    pub fn cross(&self, other: &Position) -> Position {
+        Position::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Unit vector in the same direction (the zero vector is returned unchanged)
+    pub fn unit(&self) -> Position {
+        let r = self.distance();
+        if r == 0.0 {
+            *self
+        } else {
+            *self * (1.0 / r)
+        }
+    }
+}
+
+impl Add for Position {
+    type Output = Position;
+
+    fn add(self, other: Position) -> Position {
+        Position::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Position {
+    type Output = Position;
+
+    fn sub(self, other: Position) -> Position {
+        Position::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f64> for Position {
+    type Output = Position;
+
+    fn mul(self, factor: f64) -> Position {
+        Position::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Position {
+    type Output = Position;
+
+    fn neg(self) -> Position {
+        Position::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// Position and velocity of a body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct State {
+    /// Position (AU)
+    pub position: Position,
+    /// Velocity (AU/day)
+    pub velocity: Position,
+}
+
+impl State {
+    /// Create a new state vector
+    pub fn new(position: Position, velocity: Position) -> Self {
+        Self { position, velocity }
+    }
+}
+
+impl Add for State {
+    type Output = State;
+
+    fn add(self, other: State) -> State {
+        State::new(
+            self.position + other.position,
+            self.velocity + other.velocity,
+        )
+    }
+}
+
+impl Sub for State {
+    type Output = State;
+
+    fn sub(self, other: State) -> State {
+        State::new(
+            self.position - other.position,
+            self.velocity - other.velocity,
+        )
+    }
+}
+
+impl Mul<f64> for State {
+    type Output = State;
+
+    fn mul(self, factor: f64) -> State {
+        State::new(self.position * factor, self.velocity * factor)
+    }
 }
 
 /// Represents a celestial body in the ephemeris
@@ -37,11 +143,13 @@ pub struct SpaceObject {
     /// Whether this object is active
     pub active: bool,
     /// Header data for this object
-    header_data: Vec<i32>,
+    pub(crate) header_data: Vec<i32>,
     /// Name of the object
     pub name: String,
     /// Length of coefficients for this object
     pub coefficient_length: i32,
+    /// Number of interpolated components (3 for vectors, 2 for nutations, 1 for TT-TDB)
+    pub(crate) components: usize,
 }
 
 impl SpaceObject {
@@ -51,26 +159,224 @@ impl SpaceObject {
             header_data: Vec::new(),
             name,
             coefficient_length: 0,
+            components: 3,
         }
     }
+
+    /// Whether a name refers to this object, ignoring case and underscores
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self.name.replace('_', "").eq_ignore_ascii_case(name)
+    }
 }
 
+/// Binary ephemeris data: a file, an in-memory buffer or any other
+/// seekable reader
+pub(crate) trait Source: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> Source for T {}
+
+/// Reads and caches coefficient records from the binary ephemeris file
+struct RecordReader {
+    file: Box<dyn Source>,
+    record_len: usize,
+    big_endian: bool,
+    /// Start of the first record and record length in days, against which
+    /// each record read is checked
+    start: f64,
+    interval: f64,
+    cached: Option<usize>,
+    coefficients: Vec<f64>,
+}
+
+impl RecordReader {
+    fn new(file: Box<dyn Source>) -> Self {
+        Self {
+            file,
+            record_len: 0,
+            big_endian: false,
+            start: 0.0,
+            interval: 0.0,
+            cached: None,
+            coefficients: Vec::new(),
+        }
+    }
+
+    /// Set the record layout and detect the byte order from the DENUM field
+    /// of the first header record, returning the ephemeris number
+    fn configure(&mut self, ncoeff: usize, start: f64, interval: f64) -> Result<i32> {
+        self.record_len = ncoeff;
+        self.start = start;
+        self.interval = interval;
+        self.cached = None;
+        let mut denum = [0u8; 4];
+        self.file.seek(SeekFrom::Start(DENUM_OFFSET))?;
+        self.file.read_exact(&mut denum)?;
+        let little = i32::from_le_bytes(denum);
+        self.big_endian = !(1..=10_000).contains(&little);
+        Ok(if self.big_endian {
+            i32::from_be_bytes(denum)
+        } else {
+            little
+        })
+    }
+
+    /// Length of the data in bytes
+    fn len(&mut self) -> Result<u64> {
+        Ok(self.file.seek(SeekFrom::End(0))?)
+    }
+
+    /// Read `len` bytes at byte offset `offset` of the file
+    fn read_bytes(&mut self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let mut bytes = vec![0u8; len];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode doubles in the byte order of the file
+    fn decode(&self, bytes: &[u8]) -> Vec<f64> {
+        bytes
+            .chunks_exact(8)
+            .map(|chunk| {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(chunk);
+                if self.big_endian {
+                    f64::from_be_bytes(word)
+                } else {
+                    f64::from_le_bytes(word)
+                }
+            })
+            .collect()
+    }
+
+    /// Read data record `index` (0-based, not counting the two header records)
+    ///
+    /// The record must cover its interval of the date range and hold only
+    /// finite coefficients.
+    fn read(&mut self, index: usize) -> Result<&[f64]> {
+        if self.cached != Some(index) {
+            // The cache no longer holds a checked record until this one passes
+            self.cached = None;
+            let record_bytes = self.record_len * 8;
+            let mut bytes = vec![0u8; record_bytes];
+            self.file
+                .seek(SeekFrom::Start(((index + 2) * record_bytes) as u64))?;
+            self.file.read_exact(&mut bytes)?;
+            self.coefficients = self.decode(&bytes);
+            self.check(index)?;
+            self.cached = Some(index);
+        }
+        Ok(&self.coefficients)
+    }
+
+    fn check(&self, index: usize) -> Result<()> {
+        let expected = self.start + index as f64 * self.interval;
+        let (start, end) = (self.coefficients[0], self.coefficients[1]);
+        if (start - expected).abs() > RECORD_DATE_TOLERANCE
+            || (end - expected - self.interval).abs() > RECORD_DATE_TOLERANCE
+        {
+            return Err(Error::CorruptRecord {
+                index,
+                reason: format!(
+                    "covers [{}, {}] instead of [{}, {}]",
+                    start,
+                    end,
+                    expected,
+                    expected + self.interval
+                ),
+            });
+        }
+        if let Some(k) = self.coefficients.iter().position(|c| !c.is_finite()) {
+            return Err(Error::CorruptRecord {
+                index,
+                reason: format!("has coefficient {} equal to {}", k, self.coefficients[k]),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Slack allowed in the start and end dates of a record (days)
+const RECORD_DATE_TOLERANCE: f64 = 1e-6;
+
+/// Byte offset of the DENUM field in the first record of a JPL binary ephemeris
+const DENUM_OFFSET: u64 = 2840;
+
+/// Bytes of the first header record up to and including the libration
+/// pointer, which every DE binary file carries
+const HEADER_BYTES: usize = 2856;
+
+/// Number of constant names stored in the fixed part of the first record
+const FIXED_CONSTANTS: usize = 400;
+
+/// Bodies of a DE binary file in the order of its coefficient pointers
+const DE_BODIES: [&str; 15] = [
+    "Mercury",
+    "Venus",
+    "EarthMoon_barycenter",
+    "Mars",
+    "Jupiter",
+    "Saturn",
+    "Uranus",
+    "Neptune",
+    "Pluto",
+    "Moon_geocentric",
+    "Sun",
+    "Earth_Nutations",
+    "Lunar_mantle_libration",
+    "Lunar_mantle_omega",
+    "TT_TDB",
+];
+
+/// Longest chain of SPK segment centers followed before giving up
+const MAX_CENTER_CHAIN: usize = 32;
+
+/// Ephemeris bodies by NAIF ID, used to join SPK segments to the DE file
+const NAIF_BODIES: [(i32, &str); 15] = [
+    (1, "Mercury"),
+    (199, "Mercury"),
+    (2, "Venus"),
+    (299, "Venus"),
+    (3, "EarthMoon_barycenter"),
+    (399, "Earth"),
+    (301, "Moon"),
+    (4, "Mars"),
+    (5, "Jupiter"),
+    (6, "Saturn"),
+    (7, "Uranus"),
+    (8, "Neptune"),
+    (9, "Pluto"),
+    (10, "Sun"),
+    (0, "Solar_system_barycenter"),
+];
+
 /// Main ephemeris structure
 pub struct Ephemeris {
-    config: AppConfig,
     bodies: Vec<SpaceObject>,
+    constants: HashMap<String, f64>,
+    constant_names: Vec<String>,
     start_year: i32,
     end_year: i32,
     ncoeff: i32,
     emrat: f64,
+    au: f64,
     interval: i32,
     julian_start: f64,
     julian_end: f64,
+    denum: i32,
+    records: Mutex<RecordReader>,
+    kernels: Vec<SpkKernel>,
+    naif_ids: HashMap<String, i32>,
 }
 
 impl Ephemeris {
     /// Create a new ephemeris instance
     ///
+    /// SPK kernels listed under `kernels` in the `[paths]` section of the
+    /// configuration are loaded as well. The environment is not consulted;
+    /// see [`EphemerisBuilder::env`] for the `RUST_JPL_*` overrides.
+    ///
     /// # Arguments
     /// * `config_path` - Path to the config.toml file
     ///
@@ -81,51 +387,348 @@ impl Ephemeris {
     /// # Ok::<(), rust_jpl::Error>(())
     /// ```
     pub fn new(config_path: &str) -> Result<Self> {
-        let config = AppConfig::new(config_path)?;
-        let mut eph = Self {
-            config,
+        EphemerisBuilder::new().config(config_path)?.build()
+    }
+
+    /// Open a JPL binary ephemeris without header or initial data files
+    ///
+    /// The ephemeris number, constants, date range, record length and body
+    /// set are read from the two header records of the file, so DE200,
+    /// DE405, DE421, DE430, DE440 and DE441 all load the same way. Bodies
+    /// with coefficients are active, except nutations, librations and
+    /// TT-TDB, which are not positions.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the binary ephemeris (e.g. `linux_p1550p2650.440`)
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::Ephemeris;
+    /// let eph = Ephemeris::open("assets/linux_p1550p2650.440")?;
+    /// println!("DE{}", eph.get_metadata().ephemeris_number);
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn open(path: &str) -> Result<Self> {
+        EphemerisBuilder::new().ephemeris_path(path).build()
+    }
+
+    /// Read a JPL binary ephemeris from any seekable reader, such as a file
+    /// inside an archive or custom storage
+    ///
+    /// Like [`Ephemeris::open`], everything is taken from the binary header
+    /// records. Records are read on demand, so the reader is kept.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::Ephemeris;
+    /// let file = std::io::BufReader::new(std::fs::File::open("de440.bin")?);
+    /// let eph = Ephemeris::from_reader(file)?;
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn from_reader(reader: impl Read + Seek + Send + 'static) -> Result<Self> {
+        EphemerisBuilder::new().ephemeris_reader(reader).build()
+    }
+
+    /// Read a JPL binary ephemeris held in memory
+    ///
+    /// The bytes are copied; use [`EphemerisBuilder::ephemeris_bytes`] to
+    /// hand over a `Vec<u8>` or a `&'static [u8]` without copying.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::Ephemeris;
+    /// let eph = Ephemeris::from_bytes(include_bytes!("../assets/de440s.bin"))?;
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        EphemerisBuilder::new()
+            .ephemeris_bytes(bytes.to_vec())
+            .build()
+    }
+
+    /// Load an ephemeris from its binary data and, optionally, a parsed
+    /// ASCII header and the text of an initial data file
+    ///
+    /// Without a header the date range, constants and pointers come from
+    /// the header records of the binary data. Without initial data, bodies
+    /// with coefficients are active except nutations, librations and TT-TDB.
+    pub(crate) fn load(
+        data: Box<dyn Source>,
+        header: Option<AsciiHeader>,
+        initial_data: Option<&str>,
+    ) -> Result<Self> {
+        let mut eph = Self::with_source(data);
+        match (header, initial_data) {
+            (Some(header), Some(text)) => {
+                eph.read_init_data(text);
+                eph.read_header(header);
+            }
+            (Some(header), None) => {
+                eph.bodies = DE_BODIES
+                    .iter()
+                    .map(|name| SpaceObject::new(name.to_string(), false))
+                    .collect();
+                eph.read_header(header);
+                for (slot, body) in eph.bodies.iter_mut().enumerate() {
+                    body.active = slot < 11 && pointer(body).is_ok();
+                }
+                eph.start_year = JulianDate::new(eph.julian_start).to_calendar().year;
+                eph.end_year = JulianDate::new(eph.julian_end).to_calendar().year;
+            }
+            (None, initial_data) => {
+                eph.read_binary_header()?;
+                if let Some(text) = initial_data {
+                    // Only the active flags and years of the initial data
+                    // apply to the bodies found in the binary header
+                    let bodies = std::mem::take(&mut eph.bodies);
+                    eph.read_init_data(text);
+                    let flags = std::mem::replace(&mut eph.bodies, bodies);
+                    for body in &mut eph.bodies {
+                        body.active = pointer(body).is_ok()
+                            && flags.iter().any(|f| f.active && f.matches(&body.name));
+                    }
+                }
+            }
+        }
+        eph.initialize()?;
+        Ok(eph)
+    }
+
+    fn with_source(data: Box<dyn Source>) -> Self {
+        Self {
             bodies: Vec::new(),
+            constants: HashMap::new(),
+            constant_names: Vec::new(),
             start_year: 0,
             end_year: 0,
             ncoeff: 0,
             emrat: 0.0,
+            au: 0.0,
             interval: 0,
             julian_start: 0.0,
             julian_end: 0.0,
-        };
-
-        eph.initialize()?;
-        Ok(eph)
+            denum: 0,
+            records: Mutex::new(RecordReader::new(data)),
+            kernels: Vec::new(),
+            naif_ids: SB441_N16
+                .iter()
+                .map(|(name, id)| (name.to_ascii_lowercase(), *id))
+                .collect(),
+        }
     }
 
-    /// Initialize the ephemeris by reading configuration files
+    /// Compute the coefficient lengths and prepare the record reader
     fn initialize(&mut self) -> Result<()> {
-        self.read_init_data()?;
-        self.read_header()?;
         self.calculate_coefficient_lengths();
+        let ncoeff = self.ncoeff as usize;
+        let records = self.record_count();
+        let reader = self
+            .records
+            .get_mut()
+            .map_err(|_| Error::Ephemeris("Ephemeris record cache is poisoned".to_string()))?;
+        self.denum = reader.configure(ncoeff, self.julian_start, self.interval as f64)?;
+
+        // A wrong NCOEFF or a truncated file shows in the length of the file
+        // and in the dates and coefficients of its first and last records
+        let record_bytes = 8 * ncoeff as u64;
+        let file_len = reader.len()?;
+        if record_bytes == 0 || file_len % record_bytes != 0 {
+            return Err(Error::CorruptHeader {
+                field: "NCOEFF".to_string(),
+                reason: format!(
+                    "of {} does not divide the file length {} into whole records",
+                    ncoeff, file_len
+                ),
+            });
+        }
+        let available = (file_len / record_bytes).saturating_sub(2) as usize;
+        if available < records {
+            return Err(Error::CorruptRecord {
+                index: available,
+                reason: format!(
+                    "is missing: the file holds {} records but [{}, {}] needs {}",
+                    available, self.julian_start, self.julian_end, records
+                ),
+            });
+        }
+        for index in [0, records.saturating_sub(1)] {
+            reader.read(index)?;
+        }
+        Ok(())
+    }
+
+    /// Read the constants, date range and coefficient pointers from the
+    /// header records of a binary ephemeris
+    ///
+    /// The first record holds the title, up to 400 constant names, the date
+    /// range, NCON, AU, EMRAT and the pointers of the first thirteen bodies.
+    /// Files with more than 400 constants continue the names after the
+    /// libration pointer, followed by the pointers of the lunar mantle
+    /// angular velocity and TT-TDB. The second record holds the constant
+    /// values.
+    fn read_binary_header(&mut self) -> Result<()> {
+        let reader = self
+            .records
+            .get_mut()
+            .map_err(|_| Error::Ephemeris("Ephemeris record cache is poisoned".to_string()))?;
+        let mut head = reader.read_bytes(0, HEADER_BYTES)?;
+
+        let denum = [head[2840], head[2841], head[2842], head[2843]];
+        let big_endian = !(1..=10_000).contains(&i32::from_le_bytes(denum));
+        let int = |bytes: &[u8], offset: usize| {
+            let word = [
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ];
+            if big_endian {
+                i32::from_be_bytes(word)
+            } else {
+                i32::from_le_bytes(word)
+            }
+        };
+        let double = |bytes: &[u8], offset: usize| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[offset..offset + 8]);
+            if big_endian {
+                f64::from_be_bytes(word)
+            } else {
+                f64::from_le_bytes(word)
+            }
+        };
+
+        // Names beyond the first 400 follow the pointers in the first header
+        // record and the values fill the second, so both must fit in the file
+        let file_len = reader.len()?;
+        let ncon = int(&head, 2676);
+        let extra = (ncon.max(0) as u64).saturating_sub(FIXED_CONSTANTS as u64) * 6;
+        if ncon < 0 || (HEADER_BYTES as u64 + extra + 24).max(8 * ncon as u64) > file_len / 2 {
+            return Err(Error::CorruptHeader {
+                field: "NCON".to_string(),
+                reason: format!(
+                    "of {} constants does not fit in a file of {} bytes",
+                    ncon, file_len
+                ),
+            });
+        }
+        let (ncon, extra) = (ncon as usize, extra as usize);
+        head.extend(reader.read_bytes(HEADER_BYTES as u64, extra + 24)?);
+
+        let mut pointers: Vec<[i32; 3]> = (0..12)
+            .map(|k| {
+                let at = 2696 + 12 * k;
+                [int(&head, at), int(&head, at + 4), int(&head, at + 8)]
+            })
+            .collect();
+        pointers.push([int(&head, 2844), int(&head, 2848), int(&head, 2852)]);
+        for k in 0..2 {
+            let at = HEADER_BYTES + extra + 12 * k;
+            pointers.push([int(&head, at), int(&head, at + 4), int(&head, at + 8)]);
+        }
+
+        self.bodies.clear();
+        let mut ncoeff = 2;
+        for (slot, (name, pointer)) in DE_BODIES.iter().zip(&pointers).enumerate() {
+            let components = match slot {
+                11 => 2,
+                14 => 1,
+                _ => 3,
+            };
+            // Files without the trailing pointers have zeros or unrelated
+            // data there, so only plausible pointers are kept
+            let [offset, ncf, nsub] = *pointer;
+            let present = offset >= 3 && (1..=100).contains(&ncf) && (1..=100).contains(&nsub);
+            let mut body = SpaceObject::new(name.to_string(), present && slot < 11);
+            body.components = components;
+            if present {
+                // The two header records alone take a record length each,
+                // so no coefficient lies beyond half of the file
+                let last = offset as i64 - 1 + (ncf * nsub) as i64 * components as i64;
+                if last > (file_len / 16) as i64 {
+                    return Err(Error::CorruptHeader {
+                        field: "IPT".to_string(),
+                        reason: format!(
+                            "of {} ends at coefficient {}, beyond a file of {} bytes",
+                            name, last, file_len
+                        ),
+                    });
+                }
+                body.header_data = pointer.to_vec();
+                ncoeff = ncoeff.max(last as i32);
+            }
+            self.bodies.push(body);
+        }
+
+        self.julian_start = double(&head, 2652);
+        self.julian_end = double(&head, 2660);
+        self.interval = double(&head, 2668) as i32;
+        self.au = double(&head, 2680);
+        self.emrat = double(&head, 2688);
+        self.start_year = JulianDate::new(self.julian_start).to_calendar().year;
+        self.end_year = JulianDate::new(self.julian_end).to_calendar().year;
+        if self.interval <= 0 || self.julian_end <= self.julian_start {
+            return Err(Error::Ephemeris("Not a JPL binary ephemeris".to_string()));
+        }
+
+        // Records may be padded beyond the last coefficient so that the
+        // header fits (see `export`); the file size then gives their length
+        let records = ((self.julian_end - self.julian_start) / self.interval as f64).round() as u64;
+        if file_len % (8 * (records + 2)) == 0 {
+            ncoeff = ncoeff.max((file_len / (8 * (records + 2))) as i32);
+        }
+        if (ncoeff as usize) * 8 < HEADER_BYTES + 24 {
+            return Err(Error::Ephemeris("Not a JPL binary ephemeris".to_string()));
+        }
+        if (ncoeff as usize) * 8 < head.len().max(ncon * 8) {
+            return Err(Error::CorruptHeader {
+                field: "NCON".to_string(),
+                reason: format!(
+                    "of {} constants does not fit in records of {} doubles",
+                    ncon, ncoeff
+                ),
+            });
+        }
+        self.ncoeff = ncoeff;
+
+        let names: Vec<String> = (0..ncon)
+            .map(|k| {
+                let at = if k < FIXED_CONSTANTS {
+                    252 + 6 * k
+                } else {
+                    HEADER_BYTES + 6 * (k - FIXED_CONSTANTS)
+                };
+                String::from_utf8_lossy(&head[at..at + 6])
+                    .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .to_string()
+            })
+            .collect();
+        let values = reader.read_bytes(ncoeff as u64 * 8, ncon * 8)?;
+        self.constant_names = names.clone();
+        self.constants = names
+            .into_iter()
+            .enumerate()
+            .map(|(k, name)| (name, double(&values, 8 * k)))
+            .collect();
         Ok(())
     }
 
     /// Read initial data file
-    fn read_init_data(&mut self) -> Result<()> {
-        let path = &self.config.initial_data_dat;
-        let mut indat = File::open(path)?;
-        let mut buffer = String::new();
-        indat.read_to_string(&mut buffer)?;
-        let lines: Vec<&str> = buffer.lines().collect();
+    fn read_init_data(&mut self, text: &str) {
+        let lines: Vec<&str> = text.lines().collect();
         let mut i = 0;
 
         while i < lines.len() {
             let line = lines[i];
             i += 1;
 
-            if line == "BODIES:" {
+            if line.trim() == "BODIES:" {
                 while i < lines.len() {
                     let body_line = lines[i];
-                    i += 1;
-                    if body_line == "DATE:" {
+                    if body_line.trim() == "DATE:" {
                         break;
                     }
+                    i += 1;
                     let mut parts = body_line.split_whitespace();
                     if let Some(name) = parts.next() {
                         let mut so = SpaceObject::new(name.to_string(), false);
@@ -137,7 +740,11 @@ impl Ephemeris {
                 }
             }
 
-            if line == "DATE:" {
+            if line.trim() == "DATE:" {
+                while i < lines.len() && lines[i].trim().is_empty() {
+                    i += 1;
+                }
+
                 if i < lines.len() {
                     let start_year_str = lines[i];
                     i += 1;
@@ -163,163 +770,533 @@ impl Ephemeris {
                 }
             }
         }
-
-        Ok(())
     }
 
-    /// Read header file
-    fn read_header(&mut self) -> Result<()> {
-        let path = &self.config.header_441;
-        let mut header = File::open(path)?;
-        let mut buffer = String::new();
-        header.read_to_string(&mut buffer)?;
-        let lines: Vec<&str> = buffer.lines().collect();
-        let mut number_emrat = 0;
-
-        for line in lines {
-            let mut parts = line.split_whitespace();
-            if let Some(key) = parts.next() {
-                match key {
-                    "NCOEFF=" => {
-                        self.ncoeff = parts
-                            .next()
-                            .and_then(|s| i32::from_str(s).ok())
-                            .unwrap_or(0);
-                    }
-                    "GROUP" => {
-                        if let Some(group_number_str) = parts.next() {
-                            match group_number_str {
-                                "1030" => {
-                                    if let Some(start_str) = parts.next() {
-                                        self.julian_start = f64::from_str(start_str).unwrap_or(0.0);
-                                    }
-                                    if let Some(end_str) = parts.next() {
-                                        self.julian_end = f64::from_str(end_str).unwrap_or(0.0);
-                                    }
-                                    if let Some(interval_str) = parts.next() {
-                                        self.interval = i32::from_str(interval_str).unwrap_or(0);
-                                    }
-                                }
-                                "1040" => {
-                                    if let Some(size_str) = parts.next() {
-                                        let size = i32::from_str(size_str).unwrap_or(0);
-                                        for i in 0..size {
-                                            if let Some(tmp) = parts.next() {
-                                                if tmp == "EMRAT" {
-                                                    number_emrat = i;
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                "1041" => {
-                                    for _ in 0..number_emrat {
-                                        parts.next();
-                                    }
-                                    if let Some(emrat_str) = parts.next() {
-                                        let mut emrat = emrat_str.to_string();
-                                        let len = emrat.len();
-                                        emrat.truncate(len - 4);
-                                        emrat.push('E');
-                                        self.emrat = f64::from_str(&emrat).unwrap_or(0.0);
-                                    }
-                                }
-                                "1050" => {
-                                    for _ in 0..3 {
-                                        for body in &mut self.bodies {
-                                            if let Some(buf_str) = parts.next() {
-                                                let buf = i32::from_str(buf_str).unwrap_or(0);
-                                                body.header_data.push(buf);
-                                            }
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    _ => {}
-                }
+    /// Take the date range, constants and pointers from an ASCII header
+    fn read_header(&mut self, header: AsciiHeader) {
+        self.ncoeff = header.ncoeff as i32;
+        (self.julian_start, self.julian_end) = header.range;
+        self.interval = header.interval as i32;
+        for (col, body) in self.bodies.iter_mut().enumerate() {
+            if let Some(pointer) = header.pointers.get(col) {
+                body.header_data = pointer.to_vec();
+                body.components = match col {
+                    11 => 2,
+                    14 => 1,
+                    _ => 3,
+                };
             }
         }
 
-        Ok(())
+        self.constant_names = header.constants.iter().map(|(n, _)| n.clone()).collect();
+        self.constants = header.constants.into_iter().collect();
+        self.emrat = self.constants.get("EMRAT").copied().unwrap_or(0.0);
+        self.au = self.constants.get("AU").copied().unwrap_or(0.0);
     }
 
     /// Calculate coefficient lengths for each body
     fn calculate_coefficient_lengths(&mut self) {
-        let len = self.bodies.len();
-        for i in 0..len - 1 {
-            if !self.bodies[i].header_data.is_empty() && !self.bodies[i + 1].header_data.is_empty()
-            {
-                self.bodies[i].coefficient_length =
-                    self.bodies[i + 1].header_data[0] - self.bodies[i].header_data[0];
+        for body in &mut self.bodies {
+            if let [_, ncf, nsub] = body.header_data[..] {
+                body.coefficient_length = ncf * nsub * body.components as i32;
             }
         }
-        if !self.bodies.is_empty() && !self.bodies[len - 1].header_data.is_empty() {
-            self.bodies[len - 1].coefficient_length =
-                self.ncoeff - self.bodies[len - 1].header_data[0];
+    }
+
+    fn out_of_range(&self, jd: f64) -> Error {
+        Error::DateOutOfRange {
+            jd,
+            start: self.julian_start,
+            end: self.julian_end,
         }
     }
 
-    /// Get the position of a celestial body at a given Julian date
+    /// Find an active body by name, ignoring case and underscores
+    fn find_body(&self, body_name: &str) -> Result<&SpaceObject> {
+        let body = self
+            .bodies
+            .iter()
+            .find(|b| b.matches(body_name))
+            .ok_or_else(|| Error::UnknownBody {
+                name: body_name.to_string(),
+                available: self.bodies.iter().map(|b| b.name.clone()).collect(),
+            })?;
+
+        if !body.active {
+            return Err(Error::InactiveBody {
+                name: body.name.clone(),
+            });
+        }
+
+        Ok(body)
+    }
+
+    /// Interpolate the Chebyshev series of a body, returning the component
+    /// values and their rates per day in the file's native units
+    fn interpolate(&self, body: &SpaceObject, jd: f64) -> Result<([f64; 3], [f64; 3])> {
+        let (_, _, nsub) = pointer(body)?;
+
+        let records = ((self.julian_end - self.julian_start) / self.interval as f64).round();
+        let index = ((jd - self.julian_start) / self.interval as f64)
+            .floor()
+            .clamp(0.0, (records - 1.0).max(0.0)) as usize;
+
+        let mut reader = self
+            .records
+            .lock()
+            .map_err(|_| Error::Ephemeris("Ephemeris record cache is poisoned".to_string()))?;
+        let record = reader.read(index)?;
+
+        let (start, end) = (record[0], record[1]);
+        if jd < start || jd > end {
+            return Err(Error::CorruptRecord {
+                index,
+                reason: format!(
+                    "covers [{}, {}] but Julian date {} was requested",
+                    start, end, jd
+                ),
+            });
+        }
+
+        let span = (end - start) / nsub as f64;
+        let sub = (((jd - start) / span).floor() as usize).min(nsub - 1);
+        let tc = 2.0 * (jd - (start + sub as f64 * span)) / span - 1.0;
+        series(body, record, sub, tc)
+    }
+
+    /// State of a single ephemeris body in AU and AU/day
+    fn body_state(&self, body: &SpaceObject, jd: f64) -> Result<State> {
+        if self.au <= 0.0 {
+            return Err(Error::Ephemeris(
+                "AU constant not found in header file".to_string(),
+            ));
+        }
+        let (p, v) = self.interpolate(body, jd)?;
+        let scale = 1.0 / self.au;
+        Ok(State::new(
+            Position::new(p[0], p[1], p[2]) * scale,
+            Position::new(v[0], v[1], v[2]) * scale,
+        ))
+    }
+
+    /// Get the position and velocity of a celestial body at a given Julian date
+    ///
+    /// Planets and the Sun are referred to the solar system barycenter,
+    /// `Moon_geocentric` to the Earth. `Earth` and `Moon` are also accepted and
+    /// return barycentric states derived from the Earth-Moon barycenter.
     ///
     /// # Arguments
     /// * `body_name` - Name of the celestial body (e.g., "Earth", "Moon", "Sun", "Mars")
-    /// * `jd` - Julian date
+    /// * `jd` - Julian date (TDB)
     ///
     /// # Returns
-    /// Position in AU (Astronomical Units)
+    /// State with position in AU and velocity in AU/day (ICRF axes)
+    pub fn get_state(&self, body_name: &str, jd: JulianDate) -> Result<State> {
+        // Bodies outside the DE file are chained through the SPK kernels
+        if !self.is_ephemeris_body(body_name) {
+            if let Some(id) = self.naif_id(body_name) {
+                return self.naif_state(id, jd, 0);
+            }
+        }
+
+        // Validate Julian date is within range
+        if jd.jd < self.julian_start || jd.jd > self.julian_end {
+            return Err(self.out_of_range(jd.jd));
+        }
+
+        let is_earth = body_name.eq_ignore_ascii_case("Earth");
+        if is_earth || body_name.eq_ignore_ascii_case("Moon") {
+            let emb = self.body_state(self.find_body("EarthMoon_barycenter")?, jd.jd)?;
+            let moon = self.body_state(self.find_body("Moon_geocentric")?, jd.jd)?;
+            let earth = emb - moon * (1.0 / (1.0 + self.emrat));
+            return Ok(if is_earth { earth } else { earth + moon });
+        }
+
+        let body = self.find_body(body_name)?;
+        self.body_state(body, jd.jd)
+    }
+
+    /// Get the state of a body relative to another body
     ///
     /// # Example
     /// ```ignore
     /// use rust_jpl::{Ephemeris, JulianDate};
     /// let mut eph = Ephemeris::new("config.toml")?;
+    /// eph.load_kernel("assets/sb441-n16.bsp")?;
     /// let jd = JulianDate::from_calendar(2024, 1, 15, 12, 0, 0.0)?;
-    /// let position = eph.get_position("Earth", jd)?;
+    /// let ceres = eph.get_relative_state("Ceres", "Earth", jd)?;
     /// # Ok::<(), rust_jpl::Error>(())
     /// ```
-    pub fn get_position(&self, body_name: &str, jd: JulianDate) -> Result<Position> {
-        // Validate Julian date is within range
+    pub fn get_relative_state(&self, target: &str, center: &str, jd: JulianDate) -> Result<State> {
+        Ok(self.get_state(target, jd)? - self.get_state(center, jd)?)
+    }
+
+    /// Load an SPK kernel whose bodies become available to [`get_state`]
+    ///
+    /// Kernels loaded later take precedence where coverage overlaps. Segment
+    /// centers are followed until they reach a body of the DE file or the
+    /// solar system barycenter.
+    ///
+    /// [`get_state`]: Self::get_state
+    pub fn load_kernel(&mut self, path: &str) -> Result<()> {
+        self.kernels.push(SpkKernel::open(path)?);
+        Ok(())
+    }
+
+    /// Register a name for a body found in the SPK kernels by NAIF ID
+    ///
+    /// The sixteen `sb441-n16` asteroids are registered by default, and a
+    /// NAIF ID written as a number (`"2000433"`) is always accepted.
+    pub fn register_body(&mut self, name: &str, naif_id: i32) {
+        self.naif_ids.insert(name.to_ascii_lowercase(), naif_id);
+    }
+
+    /// Whether the ephemeris can answer a state query for a body at a date
+    ///
+    /// Checks the date range and active flags of the DE file and, for
+    /// kernel bodies, the coverage of every segment in the center chain.
+    pub fn covers(&self, body_name: &str, jd: JulianDate) -> bool {
+        if !self.is_ephemeris_body(body_name) {
+            return self
+                .naif_id(body_name)
+                .is_some_and(|id| self.naif_covers(id, jd, 0));
+        }
         if jd.jd < self.julian_start || jd.jd > self.julian_end {
+            return false;
+        }
+        if body_name.eq_ignore_ascii_case("Earth") || body_name.eq_ignore_ascii_case("Moon") {
+            return self.find_body("EarthMoon_barycenter").is_ok()
+                && self.find_body("Moon_geocentric").is_ok();
+        }
+        self.find_body(body_name).is_ok()
+    }
+
+    /// Get the loaded SPK kernels
+    pub fn get_kernels(&self) -> &[SpkKernel] {
+        &self.kernels
+    }
+
+    fn is_ephemeris_body(&self, body_name: &str) -> bool {
+        body_name.eq_ignore_ascii_case("Earth")
+            || body_name.eq_ignore_ascii_case("Moon")
+            || self.bodies.iter().any(|b| b.matches(body_name))
+    }
+
+    fn naif_id(&self, body_name: &str) -> Option<i32> {
+        self.naif_ids
+            .get(&body_name.to_ascii_lowercase())
+            .copied()
+            .or_else(|| body_name.trim().parse().ok())
+    }
+
+    fn naif_covers(&self, id: i32, jd: JulianDate, depth: usize) -> bool {
+        if id == 0 {
+            return true;
+        }
+        if depth > MAX_CENTER_CHAIN {
+            return false;
+        }
+        if let Some((_, name)) = NAIF_BODIES.iter().find(|(naif, _)| *naif == id) {
+            if self.is_ephemeris_body(name) {
+                return self.covers(name, jd);
+            }
+        }
+        self.kernels
+            .iter()
+            .rev()
+            .find_map(|kernel| kernel.center(id, jd))
+            .is_some_and(|center| self.naif_covers(center, jd, depth + 1))
+    }
+
+    /// Barycentric state of a body given by NAIF ID, from the DE file if it
+    /// carries the body and otherwise from the SPK segment chain
+    fn naif_state(&self, id: i32, jd: JulianDate, depth: usize) -> Result<State> {
+        if depth > MAX_CENTER_CHAIN {
             return Err(Error::Ephemeris(format!(
-                "Julian date {} is outside valid range [{}, {}]",
-                jd.jd, self.julian_start, self.julian_end
+                "SPK segment centers for body {} do not reach the barycenter",
+                id
             )));
         }
+        if id == 0 {
+            let zero = Position::new(0.0, 0.0, 0.0);
+            return Ok(State::new(zero, zero));
+        }
+        if let Some((_, name)) = NAIF_BODIES.iter().find(|(naif, _)| *naif == id) {
+            if self.is_ephemeris_body(name) {
+                return self.get_state(name, jd);
+            }
+        }
 
-        // Find the body
-        let body = self
+        for kernel in self.kernels.iter().rev() {
+            if let Some((center, state)) = kernel.state(id, jd)? {
+                if self.au <= 0.0 {
+                    return Err(Error::Ephemeris(
+                        "AU constant not found in header file".to_string(),
+                    ));
+                }
+                let relative = State::new(
+                    state.position * (1.0 / self.au),
+                    state.velocity * (86400.0 / self.au),
+                );
+                return Ok(relative + self.naif_state(center, jd, depth + 1)?);
+            }
+        }
+
+        // Out of range for a body the kernels know, otherwise unknown
+        let coverage = self
+            .kernels
+            .iter()
+            .flat_map(|kernel| kernel.segments())
+            .filter(|segment| segment.target == id)
+            .map(|segment| (segment.start.jd, segment.end.jd))
+            .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)));
+        Err(match coverage {
+            Some((start, end)) => Error::DateOutOfRange {
+                jd: jd.jd,
+                start,
+                end,
+            },
+            None => {
+                let mut available: Vec<String> = Vec::new();
+                for segment in self.kernels.iter().flat_map(|kernel| kernel.segments()) {
+                    let target = segment.target.to_string();
+                    if !available.contains(&target) {
+                        available.push(target);
+                    }
+                }
+                Error::UnknownBody {
+                    name: id.to_string(),
+                    available,
+                }
+            }
+        })
+    }
+
+    /// Get the nutation angles `(dpsi, deps)` in radians at a given Julian date
+    ///
+    /// Returns `None` if the ephemeris does not carry nutation coefficients.
+    /// The angles are used regardless of the body's active flag.
+    pub fn get_nutation(&self, jd: JulianDate) -> Result<Option<(f64, f64)>> {
+        let body = match self
             .bodies
             .iter()
-            .find(|b| {
-                b.name.eq_ignore_ascii_case(body_name)
-                    || b.name.replace("_", "").eq_ignore_ascii_case(body_name)
-            })
-            .ok_or_else(|| {
-                Error::Ephemeris(format!(
-                    "Body '{}' not found. Available bodies: {}",
-                    body_name,
-                    self.bodies
-                        .iter()
-                        .map(|b| b.name.clone())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ))
-            })?;
+            .find(|b| b.components == 2 && b.coefficient_length > 0)
+        {
+            Some(body) => body,
+            None => return Ok(None),
+        };
 
-        if !body.active {
-            return Err(Error::Ephemeris(format!(
-                "Body '{}' is not active in this ephemeris",
-                body_name
+        if jd.jd < self.julian_start || jd.jd > self.julian_end {
+            return Err(self.out_of_range(jd.jd));
+        }
+
+        let (angles, _) = self.interpolate(body, jd.jd)?;
+        Ok(Some((angles[0], angles[1])))
+    }
+
+    /// Component values and rates per day of a body in the file's native
+    /// units (km, radians or seconds), regardless of its active flag
+    ///
+    /// Returns `None` if the file carries no coefficients for the body.
+    pub(crate) fn native_state(
+        &self,
+        body_name: &str,
+        jd: JulianDate,
+    ) -> Result<Option<([f64; 3], [f64; 3])>> {
+        let body = match self
+            .bodies
+            .iter()
+            .find(|b| b.matches(body_name) && b.coefficient_length > 0)
+        {
+            Some(body) => body,
+            None => return Ok(None),
+        };
+
+        if jd.jd < self.julian_start || jd.jd > self.julian_end {
+            return Err(self.out_of_range(jd.jd));
+        }
+
+        self.interpolate(body, jd.jd).map(Some)
+    }
+
+    /// Check every body's series for jumps at the record and sub-interval
+    /// boundaries between two Julian dates
+    ///
+    /// Each series is evaluated at both ends of every sub-interval, and the
+    /// two sides of each boundary are compared. A well-formed file shows
+    /// jumps near the rounding level; larger ones point to a corrupted file.
+    /// Returns the largest jumps of each body carried by the file, active or
+    /// not. Records that cannot be read, or whose dates do not join up, are
+    /// reported as errors.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::{Ephemeris, JulianDate};
+    /// let eph = Ephemeris::new("config.toml")?;
+    /// let start = JulianDate::from_calendar(2000, 1, 1, 0, 0, 0.0)?;
+    /// let end = JulianDate::from_calendar(2030, 1, 1, 0, 0, 0.0)?;
+    /// for jump in eph.check_continuity(start, end)? {
+    ///     println!("{}: {:.3e} at JD {}", jump.body, jump.position, jump.position_jd);
+    /// }
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn check_continuity(
+        &self,
+        start: JulianDate,
+        end: JulianDate,
+    ) -> Result<Vec<Discontinuity>> {
+        if start.jd > end.jd {
+            return Err(Error::InvalidDate(format!(
+                "Julian date {} is after {}",
+                start.jd, end.jd
             )));
         }
+        for jd in [start.jd, end.jd] {
+            if jd < self.julian_start || jd > self.julian_end {
+                return Err(self.out_of_range(jd));
+            }
+        }
 
-        // For now, return a placeholder position
-        // In a full implementation, this would read the binary ephemeris file
-        // and interpolate the Chebyshev coefficients
-        Ok(Position::new(0.0, 0.0, 0.0))
+        let bodies: Vec<&SpaceObject> = self.bodies.iter().filter(|b| pointer(b).is_ok()).collect();
+        let mut jumps: Vec<Discontinuity> = bodies
+            .iter()
+            .map(|b| Discontinuity {
+                body: b.name.clone(),
+                boundaries: 0,
+                position: 0.0,
+                position_jd: start.jd,
+                velocity: 0.0,
+                velocity_jd: start.jd,
+            })
+            .collect();
+
+        let interval = self.interval as f64;
+        let last = self.record_count().saturating_sub(1);
+        let first = (((start.jd - self.julian_start) / interval).floor() as usize).min(last);
+        let until = (((end.jd - self.julian_start) / interval).ceil() as usize).min(last);
+
+        // Values and rates of each body at the end of the previous record
+        let mut ends = vec![([0.0; 3], [0.0; 3]); bodies.len()];
+        for index in first..=until {
+            let record = self.read_record(index)?;
+            let (record_start, record_end) = (record[0], record[1]);
+            for (i, (body, jump)) in bodies.iter().zip(jumps.iter_mut()).enumerate() {
+                let (_, _, nsub) = pointer(body)?;
+                let span = (record_end - record_start) / nsub as f64;
+                let mut left = (index > first).then(|| ends[i]);
+                for sub in 0..nsub {
+                    let right = series(body, &record, sub, -1.0)?;
+                    let jd = record_start + sub as f64 * span;
+                    if let Some((values, rates)) = left {
+                        if jd >= start.jd && jd <= end.jd {
+                            jump.boundaries += 1;
+                            let position = largest_difference(&values, &right.0, body.components);
+                            let velocity = largest_difference(&rates, &right.1, body.components);
+                            if position > jump.position {
+                                jump.position = position;
+                                jump.position_jd = jd;
+                            }
+                            if velocity > jump.velocity {
+                                jump.velocity = velocity;
+                                jump.velocity_jd = jd;
+                            }
+                        }
+                    }
+                    left = Some(series(body, &record, sub, 1.0)?);
+                }
+                if let Some(end) = left {
+                    ends[i] = end;
+                }
+            }
+        }
+
+        Ok(jumps)
+    }
+
+    /// Read every data record of the file and check its dates and
+    /// coefficients, returning the number of records
+    ///
+    /// Loading checks the file length and the first and last records, and
+    /// each record is checked again when it is first used. This scans the
+    /// whole file up front, so that a damaged file is found before any
+    /// position is computed from it.
+    pub fn check_integrity(&self) -> Result<usize> {
+        let records = self.record_count();
+        for index in 0..records {
+            self.read_record(index)?;
+        }
+        Ok(records)
+    }
+
+    /// SHA-256 digest of the binary ephemeris file as lowercase hex
+    #[cfg(feature = "checksum")]
+    pub fn sha256(&self) -> Result<String> {
+        use sha2::{Digest, Sha256};
+
+        let mut reader = self
+            .records
+            .lock()
+            .map_err(|_| Error::Ephemeris("Ephemeris record cache is poisoned".to_string()))?;
+        reader.file.seek(SeekFrom::Start(0))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 1 << 20];
+        loop {
+            let read = reader.file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+
+    /// Check the SHA-256 digest of the binary ephemeris file against a
+    /// known value, such as one recorded when the file was downloaded
+    ///
+    /// rust-jpl ships no table of digests for the JPL files, so the caller
+    /// supplies the expected value. A difference is reported as
+    /// [`Error::ChecksumMismatch`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::Ephemeris;
+    /// let eph = Ephemeris::open("assets/linux_p1550p2650.440")?;
+    /// eph.verify_sha256("3b1f...")?;
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    #[cfg(feature = "checksum")]
+    pub fn verify_sha256(&self, expected: &str) -> Result<()> {
+        let actual = self.sha256()?;
+        let expected = expected.trim().to_ascii_lowercase();
+        if actual != expected {
+            return Err(Error::ChecksumMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Get the position of a celestial body at a given Julian date
+    ///
+    /// # Arguments
+    /// * `body_name` - Name of the celestial body (e.g., "Earth", "Moon", "Sun", "Mars")
+    /// * `jd` - Julian date
+    ///
+    /// # Returns
+    /// Position in AU (Astronomical Units)
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::{Ephemeris, JulianDate};
+    /// let mut eph = Ephemeris::new("config.toml")?;
+    /// let jd = JulianDate::from_calendar(2024, 1, 15, 12, 0, 0.0)?;
+    /// let position = eph.get_position("Earth", jd)?;
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn get_position(&self, body_name: &str, jd: JulianDate) -> Result<Position> {
+        Ok(self.get_state(body_name, jd)?.position)
     }
 
     /// Get all available celestial bodies
@@ -327,6 +1304,67 @@ impl Ephemeris {
         self.bodies.iter().collect()
     }
 
+    /// Get a constant from the header file by name (e.g., "AU", "EMRAT", "GMS")
+    pub fn get_constant(&self, name: &str) -> Option<f64> {
+        self.constants.get(name).copied()
+    }
+
+    /// Get the header constants in file order
+    pub fn get_constants(&self) -> Vec<(&str, f64)> {
+        self.constant_names
+            .iter()
+            .filter_map(|name| Some((name.as_str(), *self.constants.get(name)?)))
+            .collect()
+    }
+
+    /// Number of data records in the file
+    pub(crate) fn record_count(&self) -> usize {
+        ((self.julian_end - self.julian_start) / self.interval as f64).round() as usize
+    }
+
+    /// Copy of data record `index` (0-based, not counting the header records)
+    pub(crate) fn read_record(&self, index: usize) -> Result<Vec<f64>> {
+        let mut reader = self
+            .records
+            .lock()
+            .map_err(|_| Error::Ephemeris("Ephemeris record cache is poisoned".to_string()))?;
+        Ok(reader.read(index)?.to_vec())
+    }
+
+    /// Astronomical unit in km from the header
+    pub(crate) fn au(&self) -> f64 {
+        self.au
+    }
+
+    /// Get the equatorial radius of a body in km
+    ///
+    /// The Sun, Earth and Moon use the header constants `ASUN`, `RE` and `AM`
+    /// when present; other bodies use IAU nominal values.
+    pub fn get_radius(&self, body_name: &str) -> Option<f64> {
+        let name = body_name.to_ascii_lowercase().replace('_', "");
+        let header = match name.as_str() {
+            "sun" => self.get_constant("ASUN"),
+            "earth" => self.get_constant("RE"),
+            "moon" | "moongeocentric" => self.get_constant("AM"),
+            _ => None,
+        };
+
+        header.or(match name.as_str() {
+            "sun" => Some(696000.0),
+            "mercury" => Some(2440.53),
+            "venus" => Some(6051.8),
+            "earth" => Some(6378.1366),
+            "moon" | "moongeocentric" => Some(1738.0),
+            "mars" => Some(3396.19),
+            "jupiter" => Some(71492.0),
+            "saturn" => Some(60268.0),
+            "uranus" => Some(25559.0),
+            "neptune" => Some(24764.0),
+            "pluto" => Some(1188.3),
+            _ => None,
+        })
+    }
+
     /// Get the valid date range for this ephemeris
     pub fn get_date_range(&self) -> (f64, f64) {
         (self.julian_start, self.julian_end)
@@ -342,10 +1380,117 @@ impl Ephemeris {
             interval_days: self.interval as f64,
             earth_moon_ratio: self.emrat,
             number_of_coefficients: self.ncoeff,
+            ephemeris_number: self.denum,
+        }
+    }
+}
+
+/// Parse a Fortran-style floating point number (`0.1D+03` or `32.`)
+pub(crate) fn parse_fortran_f64(s: &str) -> Option<f64> {
+    f64::from_str(&s.replace(['D', 'd'], "E")).ok()
+}
+
+/// Evaluate a Chebyshev series and its derivative at `t` in [-1, 1]
+pub(crate) fn chebyshev(coefficients: &[f64], t: f64) -> (f64, f64) {
+    let mut value = 0.0;
+    let mut derivative = 0.0;
+    let (mut t_prev, mut t_curr) = (1.0, t);
+    let (mut d_prev, mut d_curr) = (0.0, 1.0);
+
+    for (k, &c) in coefficients.iter().enumerate() {
+        match k {
+            0 => value += c,
+            1 => {
+                value += c * t;
+                derivative += c;
+            }
+            _ => {
+                let t_next = 2.0 * t * t_curr - t_prev;
+                let d_next = 2.0 * t_curr + 2.0 * t * d_curr - d_prev;
+                t_prev = t_curr;
+                t_curr = t_next;
+                d_prev = d_curr;
+                d_curr = d_next;
+                value += c * t_curr;
+                derivative += c * d_curr;
+            }
+        }
+    }
+
+    (value, derivative)
+}
+
+/// Offset, coefficient count and sub-interval count of a body
+fn pointer(body: &SpaceObject) -> Result<(usize, usize, usize)> {
+    match body.header_data[..] {
+        [offset, ncf, nsub] if offset > 0 && ncf > 0 && nsub > 0 => {
+            Ok((offset as usize, ncf as usize, nsub as usize))
         }
+        _ => Err(Error::InactiveBody {
+            name: body.name.clone(),
+        }),
     }
 }
 
+/// Evaluate the series of a body in sub-interval `sub` of a record at
+/// normalized time `tc` in [-1, 1], returning values and rates per day
+fn series(body: &SpaceObject, record: &[f64], sub: usize, tc: f64) -> Result<([f64; 3], [f64; 3])> {
+    let (offset, ncf, nsub) = pointer(body)?;
+    let base = offset - 1 + sub * ncf * body.components;
+    if base + ncf * body.components > record.len() {
+        return Err(Error::CorruptHeader {
+            field: "IPT".to_string(),
+            reason: format!(
+                "of {} runs past the record length of {}",
+                body.name,
+                record.len()
+            ),
+        });
+    }
+    let span = (record[1] - record[0]) / nsub as f64;
+
+    let mut values = [0.0; 3];
+    let mut rates = [0.0; 3];
+    for component in 0..body.components {
+        let from = base + component * ncf;
+        let (value, rate) = chebyshev(&record[from..from + ncf], tc);
+        values[component] = value;
+        rates[component] = rate * 2.0 / span;
+    }
+
+    Ok((values, rates))
+}
+
+/// Largest absolute difference between the first `components` entries
+fn largest_difference(a: &[f64; 3], b: &[f64; 3], components: usize) -> f64 {
+    a.iter()
+        .zip(b)
+        .take(components)
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0, f64::max)
+}
+
+/// Largest jumps of one body's series at record and sub-interval boundaries
+///
+/// Jumps are the largest component differences between the two sides of a
+/// boundary, in the file's native units: km and km/day for positions,
+/// radians and radians/day for angles, seconds and seconds/day for TT-TDB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discontinuity {
+    /// Body name
+    pub body: String,
+    /// Number of boundaries checked
+    pub boundaries: usize,
+    /// Largest jump in value
+    pub position: f64,
+    /// Julian date of the largest jump in value
+    pub position_jd: f64,
+    /// Largest jump in rate
+    pub velocity: f64,
+    /// Julian date of the largest jump in rate
+    pub velocity_jd: f64,
+}
+
 /// Metadata about the ephemeris
 #[derive(Debug, Clone)]
 pub struct EphemerisMetadata {
@@ -356,4 +1501,511 @@ pub struct EphemerisMetadata {
     pub interval_days: f64,
     pub earth_moon_ratio: f64,
     pub number_of_coefficients: i32,
+    /// DE number read from the binary file (e.g. 441)
+    pub ephemeris_number: i32,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_chebyshev_series() {
+        // 1*T0 + 2*T1 + 3*T2 + 4*T3 = 1 + 2t + 3(2t^2 - 1) + 4(4t^3 - 3t)
+        let t = 0.3;
+        let (value, derivative) = chebyshev(&[1.0, 2.0, 3.0, 4.0], t);
+        let expected =
+            1.0 + 2.0 * t + 3.0 * (2.0 * t * t - 1.0) + 4.0 * (4.0 * t * t * t - 3.0 * t);
+        let expected_derivative = 2.0 + 12.0 * t + 4.0 * (12.0 * t * t - 3.0);
+        assert!((value - expected).abs() < 1e-12);
+        assert!((derivative - expected_derivative).abs() < 1e-12);
+    }
+
+    /// Write a little-endian DE file of one 32-day record in which the Sun
+    /// sits at (1, 0, 0) AU and the Moon pads the record to 398 doubles
+    pub(crate) fn write_binary(path: &std::path::Path) {
+        let (ncoeff, au) = (398, 149597870.7f64);
+        let mut header = vec![0u8; ncoeff * 8];
+        header[..9].copy_from_slice(b"JPL DE999");
+        for (k, name) in ["DENUM", "AU", "EMRAT"].iter().enumerate() {
+            header[252 + 6 * k..252 + 6 * k + name.len()].copy_from_slice(name.as_bytes());
+        }
+        for (k, value) in [2451536.5f64, 2451568.5, 32.0].iter().enumerate() {
+            header[2652 + 8 * k..2660 + 8 * k].copy_from_slice(&value.to_le_bytes());
+        }
+        header[2676..2680].copy_from_slice(&3i32.to_le_bytes());
+        header[2680..2688].copy_from_slice(&au.to_le_bytes());
+        header[2688..2696].copy_from_slice(&81.3f64.to_le_bytes());
+        for (slot, pointer) in [(9, [9i32, 13, 10]), (10, [3, 2, 1])] {
+            for (k, value) in pointer.iter().enumerate() {
+                let at = 2696 + 12 * slot + 4 * k;
+                header[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            }
+        }
+        header[2840..2844].copy_from_slice(&999i32.to_le_bytes());
+
+        let mut constants = vec![0.0f64; ncoeff];
+        constants[..3].copy_from_slice(&[999.0, au, 81.3]);
+        let mut record = vec![0.0f64; ncoeff];
+        record[..3].copy_from_slice(&[2451536.5, 2451568.5, au]);
+
+        let mut bytes = header;
+        for word in constants.iter().chain(&record) {
+            bytes.extend(word.to_le_bytes());
+        }
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    /// Two-body orbit in the ecliptic and equinox of J2000 (km, degrees, days)
+    pub(crate) struct ModelOrbit {
+        pub(crate) a: f64,
+        pub(crate) e: f64,
+        pub(crate) i: f64,
+        pub(crate) node: f64,
+        pub(crate) perihelion: f64,
+        /// Mean anomaly at J2000
+        pub(crate) m0: f64,
+        pub(crate) period: f64,
+    }
+
+    impl ModelOrbit {
+        const fn circular(a_au: f64, longitude: f64, period: f64) -> Self {
+            Self {
+                a: a_au * 149597870.7,
+                e: 0.0,
+                i: 0.0,
+                node: 0.0,
+                perihelion: 0.0,
+                m0: longitude,
+                period,
+            }
+        }
+
+        pub(crate) fn mean_anomaly(&self, jd: f64) -> f64 {
+            (self.m0 + 360.0 * (jd - 2451545.0) / self.period).to_radians()
+        }
+
+        /// First time after `jd` at which the mean anomaly is `mean` degrees
+        pub(crate) fn passage(&self, jd: f64, mean: f64) -> f64 {
+            let behind = (mean.to_radians() - self.mean_anomaly(jd)).rem_euclid(2.0 * PI);
+            jd + behind / (2.0 * PI) * self.period
+        }
+
+        /// ICRF position, with the ecliptic inclined by the J2000 obliquity
+        fn position(&self, jd: f64) -> [f64; 3] {
+            let m = self.mean_anomaly(jd);
+            let mut anomaly = m;
+            for _ in 0..50 {
+                anomaly = m + self.e * anomaly.sin();
+            }
+            let x = self.a * (anomaly.cos() - self.e);
+            let y = self.a * (1.0 - self.e * self.e).sqrt() * anomaly.sin();
+
+            let ecliptic = crate::frames::rotation_z(-self.node.to_radians())
+                .mul(&crate::frames::rotation_x(-self.i.to_radians()))
+                .mul(&crate::frames::rotation_z(-self.perihelion.to_radians()))
+                .apply(&Position::new(x, y, 0.0));
+            let p = crate::frames::rotation_x(-crate::frames::mean_obliquity(crate::frames::J2000))
+                .apply(&ecliptic);
+            [p.x, p.y, p.z]
+        }
+    }
+
+    /// Circular, coplanar orbits of Venus, the Earth-Moon barycenter and
+    /// Mars around a fixed Sun
+    pub(crate) const MODEL_VENUS: ModelOrbit = ModelOrbit::circular(0.723332, 181.98, 224.701);
+    pub(crate) const MODEL_EMB: ModelOrbit = ModelOrbit::circular(1.0, 100.46, 365.25636);
+    pub(crate) const MODEL_MARS: ModelOrbit = ModelOrbit::circular(1.523679, 355.45, 686.98);
+
+    /// Fixed Keplerian geocentric orbit of the Moon
+    pub(crate) const MODEL_MOON: ModelOrbit = ModelOrbit {
+        a: 384400.0,
+        e: 0.0549,
+        i: 5.145,
+        node: 125.04,
+        perihelion: 318.15,
+        m0: 134.96,
+        period: 27.321661,
+    };
+
+    /// Bytes of a DE file of 25 records from JD 2451536.5 holding the model
+    /// orbits, with a speed of light high enough that apparent positions
+    /// are geometric
+    pub(crate) fn model_bytes() -> Vec<u8> {
+        let (au, emrat, interval, records) = (149597870.7f64, 81.3, 32.0, 25);
+        let start = 2451536.5;
+        // Slot, orbit (none for the Sun), coefficients and sub-intervals
+        let bodies = [
+            (1, Some(&MODEL_VENUS), 13, 2),
+            (2, Some(&MODEL_EMB), 13, 2),
+            (3, Some(&MODEL_MARS), 13, 2),
+            (9, Some(&MODEL_MOON), 13, 8),
+            (10, None, 2, 1),
+        ];
+
+        let mut pointers = [[0i32; 3]; crate::export::POINTERS];
+        let mut offset = 3;
+        for &(slot, _, ncf, nsub) in &bodies {
+            pointers[slot] = [offset, ncf, nsub];
+            offset += 3 * ncf * nsub;
+        }
+        // GMB consistent with the period of the model Moon (au^3/day^2)
+        let gmb = (2.0 * PI / MODEL_MOON.period).powi(2) * (MODEL_MOON.a / au).powi(3);
+        let constants = [
+            ("DENUM", 998.0),
+            ("AU", au),
+            ("EMRAT", emrat),
+            ("CLIGHT", 1e12),
+            ("GMB", gmb),
+            ("RE", 6378.1366),
+            ("AM", 1738.0),
+            ("ASUN", 696000.0),
+        ];
+        let header = crate::export::BinaryHeader {
+            titles: [
+                "JPL DE998 analytic model".to_string(),
+                String::new(),
+                String::new(),
+            ],
+            constants: constants.iter().map(|(n, v)| (n.to_string(), *v)).collect(),
+            range: [start, start + interval * records as f64, interval],
+            au,
+            emrat,
+            denum: 998,
+            pointers,
+        };
+        let ncoeff = header.record_length(offset as usize - 1);
+
+        let mut bytes = header.encode(ncoeff);
+        for k in 0..records {
+            let first = start + interval * k as f64;
+            let mut record = vec![0.0f64; ncoeff];
+            record[0] = first;
+            record[1] = first + interval;
+            for &(slot, orbit, ncf, nsub) in &bodies {
+                let Some(orbit) = orbit else { continue };
+                let (at, ncf, nsub) = (pointers[slot][0] as usize, ncf as usize, nsub as usize);
+                let length = interval / nsub as f64;
+                // Interpolate at the Chebyshev nodes of each sub-interval
+                for sub in 0..nsub {
+                    let nodes: Vec<f64> = (0..ncf)
+                        .map(|j| (PI * (j as f64 + 0.5) / ncf as f64).cos())
+                        .collect();
+                    let values: Vec<[f64; 3]> = nodes
+                        .iter()
+                        .map(|x| orbit.position(first + length * (sub as f64 + 0.5 * (x + 1.0))))
+                        .collect();
+                    for component in 0..3 {
+                        for degree in 0..ncf {
+                            let sum: f64 = nodes
+                                .iter()
+                                .zip(&values)
+                                .map(|(x, v)| v[component] * (degree as f64 * x.acos()).cos())
+                                .sum();
+                            let scale = if degree == 0 { 1.0 } else { 2.0 };
+                            let index = at - 1 + (sub * 3 + component) * ncf + degree;
+                            record[index] = scale * sum / ncf as f64;
+                        }
+                    }
+                }
+            }
+            for word in record {
+                bytes.extend(word.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Ephemeris read from [`model_bytes`]
+    pub(crate) fn model() -> Ephemeris {
+        Ephemeris::from_bytes(&model_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_model() {
+        let eph = model();
+        let jd = JulianDate::new(2451700.25);
+        let expected = MODEL_MOON.position(jd.jd);
+        let moon = eph.get_position("Moon_geocentric", jd).unwrap() * 149597870.7;
+        assert!((moon.x - expected[0]).abs() < 1e-3 && (moon.z - expected[2]).abs() < 1e-3);
+        let mars = eph.get_position("Mars", jd).unwrap().distance();
+        assert!((mars - 1.523679).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_open_binary() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_de_{}.bin", std::process::id()));
+        write_binary(&path);
+        let eph = Ephemeris::open(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let metadata = eph.get_metadata();
+        assert_eq!(metadata.ephemeris_number, 999);
+        assert_eq!(metadata.number_of_coefficients, 398);
+        assert_eq!(metadata.interval_days, 32.0);
+        assert_eq!(eph.get_constant("EMRAT"), Some(81.3));
+
+        let active: Vec<&str> = eph
+            .get_bodies()
+            .into_iter()
+            .filter(|b| b.active)
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(active, ["Moon_geocentric", "Sun"]);
+        let sun = eph.get_position("Sun", JulianDate::new(2451545.0)).unwrap();
+        assert!((sun.x - 1.0).abs() < 1e-15 && sun.y == 0.0);
+        assert!(matches!(
+            eph.get_position("Mars", JulianDate::new(2451545.0)),
+            Err(Error::InactiveBody { name }) if name == "Mars"
+        ));
+        assert!(matches!(
+            eph.get_position("Vulcan", JulianDate::new(2451545.0)),
+            Err(Error::UnknownBody { available, .. }) if available.len() == 15
+        ));
+        assert!(matches!(
+            eph.get_position("Sun", JulianDate::new(2451600.0)),
+            Err(Error::DateOutOfRange { end, .. }) if end == 2451568.5
+        ));
+
+        // The file context is displayed with the error it wraps, so it does
+        // not report that error again as its source
+        let missing = Ephemeris::open("no_such_file.bin").err().unwrap();
+        assert!(std::error::Error::source(&missing).is_none());
+        assert!(matches!(missing.root(), Error::Io(_)));
+        assert!(missing
+            .to_string()
+            .starts_with("no_such_file.bin: I/O error"));
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_mem_{}.bin", std::process::id()));
+        write_binary(&path);
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let jd = JulianDate::new(2451550.0);
+        let from_bytes = Ephemeris::from_bytes(&bytes).unwrap();
+        let from_reader = Ephemeris::from_reader(std::io::Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(from_bytes.get_metadata().ephemeris_number, 999);
+        assert_eq!(
+            from_bytes.get_state("Sun", jd).unwrap(),
+            from_reader.get_state("Sun", jd).unwrap()
+        );
+
+        let truncated = Ephemeris::from_bytes(&bytes[..bytes.len() - 8])
+            .err()
+            .unwrap();
+        assert!(!matches!(truncated, Error::File { .. }));
+        assert!(Ephemeris::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_ncon() {
+        let bytes = model_bytes();
+        let ncoeff = model().get_metadata().number_of_coefficients;
+        for ncon in [-1, i32::MAX, ncoeff + 1] {
+            let mut corrupt = bytes.clone();
+            corrupt[2676..2680].copy_from_slice(&ncon.to_le_bytes());
+            assert!(matches!(
+                Ephemeris::from_bytes(&corrupt),
+                Err(Error::CorruptHeader { field, .. }) if field == "NCON"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_corrupt_pointer() {
+        let bytes = model_bytes();
+        // Offset of the Sun, slot 10 of the first pointer block
+        let at = 2696 + 12 * 10;
+        for offset in [i32::MAX, bytes.len() as i32 / 8] {
+            let mut corrupt = bytes.clone();
+            corrupt[at..at + 4].copy_from_slice(&offset.to_le_bytes());
+            assert!(matches!(
+                Ephemeris::from_bytes(&corrupt),
+                Err(Error::CorruptHeader { field, .. }) if field == "IPT"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_continuity() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_jump_{}.bin", std::process::id()));
+        write_binary(&path);
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[2660..2668].copy_from_slice(&2451600.5f64.to_le_bytes());
+        let mut record = vec![0.0f64; 398];
+        record[..3].copy_from_slice(&[2451568.5, 2451600.5, 149597870.7 + 1.0]);
+        for word in &record {
+            bytes.extend(word.to_le_bytes());
+        }
+        std::fs::write(&path, bytes).unwrap();
+        let eph = Ephemeris::open(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let jumps = eph
+            .check_continuity(JulianDate::new(2451536.5), JulianDate::new(2451600.5))
+            .unwrap();
+        let summary: Vec<(&str, usize, f64, f64)> = jumps
+            .iter()
+            .map(|j| (j.body.as_str(), j.boundaries, j.position, j.position_jd))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Moon_geocentric", 19, 0.0, 2451536.5),
+                ("Sun", 1, 1.0, 2451568.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_integrity() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_bad_{}.bin", std::process::id()));
+        let open = |edit: &dyn Fn(&mut Vec<u8>)| {
+            write_binary(&path);
+            let mut bytes = std::fs::read(&path).unwrap();
+            edit(&mut bytes);
+            std::fs::write(&path, bytes).unwrap();
+            Ephemeris::open(path.to_str().unwrap())
+        };
+        fn message<T>(result: Result<T>) -> String {
+            match result {
+                Err(err) => err.root().to_string(),
+                Ok(_) => String::new(),
+            }
+        }
+
+        let short = open(&|bytes| bytes.truncate(bytes.len() - 8));
+        assert!(matches!(
+            short.err().unwrap().root(),
+            Error::CorruptHeader { field, .. } if field == "NCOEFF"
+        ));
+        let long_range = open(&|bytes| {
+            bytes[2660..2668].copy_from_slice(&2451600.5f64.to_le_bytes());
+        });
+        assert!(matches!(
+            long_range.err().unwrap().root(),
+            Error::CorruptRecord { index: 1, .. }
+        ));
+        let shifted = open(&|bytes| {
+            bytes[6368..6376].copy_from_slice(&2451504.5f64.to_le_bytes());
+        });
+        assert!(message(shifted).contains("covers [2451504.5, 2451568.5]"));
+        let nan = open(&|bytes| {
+            bytes[6368 + 80..6368 + 88].copy_from_slice(&f64::NAN.to_le_bytes());
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            nan.err().unwrap().root(),
+            Error::CorruptRecord { index: 0, .. }
+        ));
+
+        // Records between the first and last are checked on first use
+        let mut bytes = model_bytes();
+        let ncoeff = model().get_metadata().number_of_coefficients as usize;
+        let at = (12 + 2) * ncoeff * 8 + 80;
+        bytes[at..at + 8].copy_from_slice(&f64::INFINITY.to_le_bytes());
+        let eph = Ephemeris::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            eph.check_integrity(),
+            Err(Error::CorruptRecord { index: 12, .. })
+        ));
+        assert!(eph
+            .get_position("Venus", JulianDate::new(2451930.5))
+            .is_err());
+        assert!(eph
+            .get_position("Venus", JulianDate::new(2451900.0))
+            .is_ok());
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_sha256() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_sha_{}.bin", std::process::id()));
+        write_binary(&path);
+        let eph = Ephemeris::open(path.to_str().unwrap()).unwrap();
+        let digest = eph.sha256().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(digest.len(), 64);
+        assert!(eph.verify_sha256(&digest.to_uppercase()).is_ok());
+        let zeros = "0".repeat(64);
+        assert!(matches!(
+            eph.verify_sha256(&zeros),
+            Err(Error::ChecksumMismatch { expected, actual }) if expected == zeros && actual == digest
+        ));
+    }
+
+    #[test]
+    fn test_parse_fortran_f64() {
+        assert_eq!(
+            parse_fortran_f64("0.8130056822149722D+02"),
+            Some(81.30056822149722)
+        );
+        assert_eq!(parse_fortran_f64("32."), Some(32.0));
+    }
+
+    #[test]
+    fn test_kernel_bodies() {
+        let path =
+            std::env::temp_dir().join(format!("rust_jpl_kernel_bodies_{}.bsp", std::process::id()));
+        crate::spk::tests::write_kernel(
+            &path,
+            &[
+                (2000001, 10, 1.0),
+                (2000002, 0, 2.0),
+                (2000003, 2000001, 0.5),
+                (2000005, 2000006, 1.0),
+                (2000006, 2000005, 1.0),
+                (2000007, 2000099, 1.0),
+            ],
+        );
+        let mut eph = model();
+        eph.load_kernel(path.to_str().unwrap()).unwrap();
+        eph.register_body("Probe", 2000003);
+        std::fs::remove_file(&path).unwrap();
+
+        let jd = JulianDate::new(2451546.75);
+        let au = eph.au();
+        let segment = |id: i32| {
+            let (_, state) = eph.get_kernels()[0].state(id, jd).unwrap().unwrap();
+            State::new(state.position * (1.0 / au), state.velocity * (86400.0 / au))
+        };
+        let close = |a: State, b: State| {
+            (a.position - b.position).distance() < 1e-12
+                && (a.velocity - b.velocity).distance() < 1e-12
+        };
+        let sun = eph.get_state("Sun", jd).unwrap();
+        let earth = eph.get_state("Earth", jd).unwrap();
+
+        // Ceres is referred to the Sun of the DE file, Pallas to the barycenter
+        let ceres = eph.get_relative_state("Ceres", "Earth", jd).unwrap();
+        assert!(close(ceres, segment(2000001) + sun - earth));
+        let pallas = eph.get_relative_state("Pallas", "Earth", jd).unwrap();
+        assert!(close(pallas, segment(2000002) - earth));
+
+        // A registered name and a numeric ID follow the chain through Ceres
+        let probe = segment(2000003) + segment(2000001) + sun;
+        assert!(close(eph.get_state("Probe", jd).unwrap(), probe));
+        assert!(close(eph.get_state("2000003", jd).unwrap(), probe));
+        assert!(eph.covers("Probe", jd));
+        assert!(!eph.covers("Probe", JulianDate::new(2451548.0)));
+
+        // Centers that loop or lead to no segment never reach the barycenter
+        let cycle = eph.get_state("2000005", jd).unwrap_err();
+        assert!(cycle.to_string().contains("do not reach the barycenter"));
+        assert!(!eph.covers("2000005", jd));
+        assert!(matches!(
+            eph.get_state("2000007", jd),
+            Err(Error::UnknownBody { name, available }) if name == "2000099" && available.len() == 6
+        ));
+        assert!(!eph.covers("2000007", jd));
+        assert!(matches!(
+            eph.get_state("Ceres", JulianDate::new(2451548.0)),
+            Err(Error::DateOutOfRange { start, end, .. }) if (start, end) == (2451545.0, 2451547.0)
+        ));
+    }
 }
@@ -0,0 +1,231 @@
+//! Several ephemerides queried in priority order
+//!
+//! A typical set pairs a short, precise ephemeris such as DE440 with a long
+//! one such as DE441: queries inside the DE440 span are answered by DE440
+//! and the rest fall back to DE441.
+
+use crate::ephemeris::{Ephemeris, Position, State};
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Ephemerides in priority order, each identified by a label
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::{Ephemeris, EphemerisSet, JulianDate};
+/// let set = EphemerisSet::new()
+///     .with_source("DE440", Ephemeris::new("de440.toml")?)
+///     .with_source("DE441", Ephemeris::new("de441.toml")?);
+/// let jd = JulianDate::from_calendar(1066, 10, 14, 12, 0, 0.0)?;
+/// let (state, source) = set.get_state("Mars", jd)?;
+/// println!("Mars from {}: {:.6} AU", source, state.position.distance());
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+#[derive(Default)]
+pub struct EphemerisSet {
+    sources: Vec<(String, Ephemeris)>,
+}
+
+impl EphemerisSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source with lower priority than those already in the set
+    pub fn with_source(mut self, label: &str, eph: Ephemeris) -> Self {
+        self.add_source(label, eph);
+        self
+    }
+
+    /// Add a source with lower priority than those already in the set
+    pub fn add_source(&mut self, label: &str, eph: Ephemeris) {
+        self.sources.push((label.to_string(), eph));
+    }
+
+    /// Labels of the sources in priority order
+    pub fn labels(&self) -> Vec<&str> {
+        self.sources
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+
+    /// Get a source by label
+    pub fn get_source(&self, label: &str) -> Option<&Ephemeris> {
+        self.sources
+            .iter()
+            .find(|(name, _)| name == label)
+            .map(|(_, eph)| eph)
+    }
+
+    /// First source that covers a body at a Julian date, with its label
+    ///
+    /// The returned ephemeris can be passed to any function of the crate
+    /// that takes an [`Ephemeris`].
+    pub fn source_for(&self, body_name: &str, jd: JulianDate) -> Option<(&str, &Ephemeris)> {
+        self.sources
+            .iter()
+            .find(|(_, eph)| eph.covers(body_name, jd))
+            .map(|(label, eph)| (label.as_str(), eph))
+    }
+
+    /// Get the state of a body from the first source that covers it,
+    /// together with the label of that source
+    pub fn get_state(&self, body_name: &str, jd: JulianDate) -> Result<(State, &str)> {
+        let (label, eph) = self.require(body_name, jd)?;
+        Ok((eph.get_state(body_name, jd)?, label))
+    }
+
+    /// Get the position of a body from the first source that covers it,
+    /// together with the label of that source
+    pub fn get_position(&self, body_name: &str, jd: JulianDate) -> Result<(Position, &str)> {
+        let (state, label) = self.get_state(body_name, jd)?;
+        Ok((state.position, label))
+    }
+
+    /// Get the state of a body relative to another, both taken from the
+    /// first source that covers the two of them
+    pub fn get_relative_state(
+        &self,
+        target: &str,
+        center: &str,
+        jd: JulianDate,
+    ) -> Result<(State, &str)> {
+        let (label, eph) = self
+            .sources
+            .iter()
+            .find(|(_, eph)| eph.covers(target, jd) && eph.covers(center, jd))
+            .ok_or_else(|| self.uncovered(&[target, center], jd))?;
+        Ok((eph.get_relative_state(target, center, jd)?, label.as_str()))
+    }
+
+    /// Union of the date ranges of all sources
+    pub fn get_date_range(&self) -> Option<(f64, f64)> {
+        self.sources
+            .iter()
+            .map(|(_, eph)| eph.get_date_range())
+            .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+    }
+
+    fn require(&self, body_name: &str, jd: JulianDate) -> Result<(&str, &Ephemeris)> {
+        self.source_for(body_name, jd)
+            .ok_or_else(|| self.uncovered(&[body_name], jd))
+    }
+
+    /// Error for a query that no source answers: `UnknownBody` when no
+    /// source knows a body, `DateOutOfRange` over the union of the sources
+    /// when the date is outside all of them, and otherwise the error of the
+    /// first source
+    fn uncovered(&self, bodies: &[&str], jd: JulianDate) -> Error {
+        let Some((start, end)) = self.get_date_range() else {
+            return Error::Config("Ephemeris set has no sources".to_string());
+        };
+        for body in bodies {
+            let mut available: Vec<String> = Vec::new();
+            for (_, eph) in &self.sources {
+                match eph.get_state(body, jd) {
+                    Err(Error::UnknownBody {
+                        available: names, ..
+                    }) => {
+                        for name in names {
+                            if !available.contains(&name) {
+                                available.push(name);
+                            }
+                        }
+                    }
+                    _ => {
+                        available.clear();
+                        break;
+                    }
+                }
+            }
+            if !available.is_empty() {
+                return Error::UnknownBody {
+                    name: body.to_string(),
+                    available,
+                };
+            }
+        }
+        if jd.jd < start || jd.jd > end {
+            return Error::DateOutOfRange {
+                jd: jd.jd,
+                start,
+                end,
+            };
+        }
+        let (_, first) = &self.sources[0];
+        bodies
+            .iter()
+            .find_map(|body| first.get_state(body, jd).err())
+            .unwrap_or(Error::DateOutOfRange {
+                jd: jd.jd,
+                start,
+                end,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::{model, write_binary};
+
+    /// A one-record file with the Sun at (1, 0, 0) AU, then the 800-day
+    /// model with the Sun at the origin and the planets
+    fn sources(name: &str) -> EphemerisSet {
+        let file = format!("rust_jpl_set_{}_{}.bin", name, std::process::id());
+        let path = std::env::temp_dir().join(file);
+        write_binary(&path);
+        let short = Ephemeris::open(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        EphemerisSet::new()
+            .with_source("short", short)
+            .with_source("model", model())
+    }
+
+    #[test]
+    fn test_fallback_order() {
+        let set = sources("fallback");
+        assert_eq!(set.labels(), ["short", "model"]);
+        assert_eq!(set.get_date_range(), Some((2451536.5, 2452336.5)));
+        assert!(set.get_source("model").is_some() && set.get_source("DE441").is_none());
+
+        let (sun, label) = set.get_position("Sun", JulianDate::new(2451545.0)).unwrap();
+        assert_eq!((sun.x, label), (1.0, "short"));
+        let (sun, label) = set.get_position("Sun", JulianDate::new(2451600.0)).unwrap();
+        assert_eq!((sun.x, label), (0.0, "model"));
+
+        // Mars is inactive in the first source, so the model answers
+        let jd = JulianDate::new(2451545.0);
+        assert_eq!(set.source_for("Mars", jd).unwrap().0, "model");
+        assert_eq!(
+            set.get_relative_state("Mars", "Sun", jd).unwrap().1,
+            "model"
+        );
+        assert_eq!(set.get_state("Sun", jd).unwrap().1, "short");
+    }
+
+    #[test]
+    fn test_uncovered_queries() {
+        let mut set = sources("uncovered");
+        assert!(matches!(
+            set.get_state("Sun", JulianDate::new(2460000.5)),
+            Err(Error::DateOutOfRange { start, end, .. }) if (start, end) == (2451536.5, 2452336.5)
+        ));
+        assert!(matches!(
+            set.get_relative_state("Vulcan", "Sun", JulianDate::new(2451545.0)),
+            Err(Error::UnknownBody { name, .. }) if name == "Vulcan"
+        ));
+
+        set.sources.truncate(1);
+        assert!(matches!(
+            set.get_state("Mars", JulianDate::new(2451545.0)),
+            Err(Error::InactiveBody { name }) if name == "Mars"
+        ));
+        assert!(matches!(
+            EphemerisSet::new().get_state("Sun", JulianDate::new(2451545.0)),
+            Err(Error::Config(_))
+        ));
+    }
+}
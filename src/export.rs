@@ -0,0 +1,623 @@
+//! Trimmed copies of an ephemeris
+//!
+//! A [`Subset`] selects a date range and a set of bodies from a loaded
+//! ephemeris and writes them either as a JPL binary file, readable with
+//! [`Ephemeris::open`], or as an SPK kernel of type 2 segments, readable
+//! with [`SpkKernel::open`](crate::spk::SpkKernel::open) and SPICE. Records
+//! are copied whole, so the subset reproduces the original exactly, unless a
+//! tolerance is set to drop the smallest Chebyshev terms.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::ephemeris::Ephemeris;
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Bytes of the fixed part of the first header record of a binary file
+const HEADER_BYTES: usize = 2856;
+
+/// Number of constant names stored in the fixed part of the first record
+const FIXED_CONSTANTS: usize = 400;
+
+/// Number of coefficient pointers in a binary file header
+pub(crate) const POINTERS: usize = 15;
+
+/// Size of a DAF record (bytes)
+const RECORD_BYTES: usize = 1024;
+
+/// Julian date of the J2000 epoch, the origin of SPK ephemeris time
+const J2000: f64 = 2451545.0;
+
+/// Seconds per day
+const DAY_SECONDS: f64 = 86400.0;
+
+/// DAF file transfer validation string, used by SPICE to detect files
+/// damaged by text-mode transfers
+const FTP_STRING: &[u8; 28] = b"FTPSTR:\r:\n:\r\n:\r\x00:\x81:\x10\xce:ENDFTP";
+
+/// Last body slot holding positions (the Sun); later slots are nutations,
+/// librations and TT-TDB
+const LAST_POSITION_SLOT: usize = 10;
+
+/// Fewest coefficients kept per series, so that velocities do not vanish
+const MIN_COEFFICIENTS: usize = 2;
+
+/// Output file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// JPL binary ephemeris with its header records
+    JplBinary,
+    /// SPICE SPK kernel with Chebyshev (type 2) segments
+    Spk,
+}
+
+/// Date range and bodies to copy out of an ephemeris
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::export::{ExportFormat, Subset};
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(1900, 1, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2100, 1, 1, 0, 0, 0.0)?;
+/// Subset::new(start, end)
+///     .with_bodies(&["Sun", "Earth", "Moon", "Mars"])
+///     .write(&eph, "de441_1900_2100.bin", ExportFormat::JplBinary)?;
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subset {
+    start: JulianDate,
+    end: JulianDate,
+    bodies: Vec<String>,
+    tolerance: Option<f64>,
+}
+
+impl Subset {
+    /// Select the records covering `[start, end]` and every body
+    pub fn new(start: JulianDate, end: JulianDate) -> Self {
+        Self {
+            start,
+            end,
+            bodies: Vec::new(),
+            tolerance: None,
+        }
+    }
+
+    /// Restrict the subset to some bodies
+    ///
+    /// `Earth` and `Moon` select both the Earth-Moon barycenter and the
+    /// geocentric Moon, from which either is derived.
+    pub fn with_bodies(mut self, bodies: &[&str]) -> Self {
+        self.bodies = bodies.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Shorten the Chebyshev series of the Sun, Moon and planets so that
+    /// positions stay within `km` of the source on every coordinate
+    ///
+    /// Since Chebyshev polynomials are bounded by 1, the dropped terms of a
+    /// series can move a coordinate by at most the sum of their magnitudes.
+    /// Each body keeps the fewest coefficients for which that sum is within
+    /// the tolerance in every record of the subset. Nutations, librations
+    /// and TT-TDB are copied whole.
+    pub fn with_tolerance(mut self, km: f64) -> Self {
+        self.tolerance = Some(km);
+        self
+    }
+
+    /// Write the subset in the given format
+    pub fn write(&self, eph: &Ephemeris, path: &str, format: ExportFormat) -> Result<()> {
+        match format {
+            ExportFormat::JplBinary => self.write_binary(eph, path),
+            ExportFormat::Spk => self.write_spk(eph, path),
+        }
+    }
+
+    /// Write the subset as a JPL binary ephemeris
+    ///
+    /// The header keeps the constants and DE number of the source and
+    /// records the new date range and coefficient pointers. Records are
+    /// padded when the selected bodies are too few for the header to fit.
+    pub fn write_binary(&self, eph: &Ephemeris, path: &str) -> Result<()> {
+        let metadata = eph.get_metadata();
+        let bodies = eph.get_bodies();
+        let (first, count) = self.records(eph)?;
+
+        let mut pointers = [[0i32; 3]; POINTERS];
+        // (source offset, output offset, source and kept coefficients, series)
+        let mut sources = Vec::new();
+        let mut next = 3;
+        for slot in self.slots(eph)? {
+            let body = bodies[slot];
+            let (offset, ncf, nsub) = (
+                body.header_data[0] as usize,
+                body.header_data[1] as usize,
+                body.header_data[2] as usize,
+            );
+            let kept = self.kept_coefficients(eph, slot, first, count)?;
+            let series = nsub * body.components;
+            pointers[slot] = [next as i32, kept as i32, nsub as i32];
+            sources.push((offset - 1, next - 1, ncf, kept, series));
+            next += kept * series;
+        }
+
+        let interval = metadata.interval_days;
+        let start = metadata.julian_start + first as f64 * interval;
+        let end = start + count as f64 * interval;
+        let header = BinaryHeader {
+            titles: [
+                format!(
+                    "JPL Planetary Ephemeris DE{} subset",
+                    metadata.ephemeris_number
+                ),
+                format!("Start Epoch: JED= {:.1}", start),
+                format!("Final Epoch: JED= {:.1}", end),
+            ],
+            constants: eph
+                .get_constants()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            range: [start, end, interval],
+            au: eph.au(),
+            emrat: metadata.earth_moon_ratio,
+            denum: metadata.ephemeris_number,
+            pointers,
+        };
+        let ncoeff = header.record_length(next - 1);
+
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(&header.encode(ncoeff))?;
+        for index in first..first + count {
+            let source = eph.read_record(index)?;
+            let mut record = vec![0.0; ncoeff];
+            record[..2].copy_from_slice(&source[..2]);
+            for &(from, to, ncf, kept, series) in &sources {
+                for k in 0..series {
+                    let (from, to) = (from + k * ncf, to + k * kept);
+                    record[to..to + kept].copy_from_slice(&source[from..from + kept]);
+                }
+            }
+            write_doubles(&mut out, &record)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Write the subset as an SPK kernel
+    ///
+    /// Planet barycenters and the Sun are referred to the solar system
+    /// barycenter (NAIF 0); the Earth (399) and Moon (301) to the Earth-Moon
+    /// barycenter (3), as in the SPKs distributed by JPL. Each sub-interval
+    /// of the source becomes one Chebyshev record. Selecting the Moon
+    /// always writes the Earth-Moon barycenter too, so that the Earth and
+    /// Moon can be referred to the solar system barycenter.
+    pub fn write_spk(&self, eph: &Ephemeris, path: &str) -> Result<()> {
+        let metadata = eph.get_metadata();
+        let bodies = eph.get_bodies();
+        let (first, count) = self.records(eph)?;
+        let emrat = metadata.earth_moon_ratio;
+
+        let mut slots = self.slots(eph)?;
+        if slots.contains(&9) && !slots.contains(&2) {
+            if bodies[2].header_data[1] == 0 {
+                return Err(Error::InactiveBody {
+                    name: bodies[2].name.clone(),
+                });
+            }
+            slots.push(2);
+            slots.sort_unstable();
+        }
+
+        // (target, center, slot, scale of the coefficients)
+        let mut segments = Vec::new();
+        for slot in slots {
+            match slot {
+                0..=8 => segments.push((slot as i32 + 1, 0, slot, 1.0)),
+                9 => {
+                    segments.push((301, 3, slot, emrat / (1.0 + emrat)));
+                    segments.push((399, 3, slot, -1.0 / (1.0 + emrat)));
+                }
+                10 => segments.push((10, 0, slot, 1.0)),
+                _ => {
+                    return Err(Error::Config(format!(
+                        "{} cannot be written to an SPK",
+                        bodies[slot].name
+                    )))
+                }
+            }
+        }
+
+        let label = format!("DE{} subset", metadata.ephemeris_number);
+        let interval = metadata.interval_days;
+        let start = metadata.julian_start + first as f64 * interval;
+        let end = start + count as f64 * interval;
+        let to_et = |jd: f64| (jd - J2000) * DAY_SECONDS;
+
+        let mut data = Vec::new();
+        let mut summaries = Vec::new();
+        for &(target, center, slot, scale) in &segments {
+            let body = bodies[slot];
+            let (offset, ncf, nsub) = (
+                body.header_data[0] as usize,
+                body.header_data[1] as usize,
+                body.header_data[2] as usize,
+            );
+            let kept = self.kept_coefficients(eph, slot, first, count)?;
+            let span = interval / nsub as f64;
+            let begin = 3 * RECORD_BYTES / 8 + 1 + data.len();
+
+            for index in first..first + count {
+                let source = eph.read_record(index)?;
+                for sub in 0..nsub {
+                    data.push(to_et(source[0] + (sub as f64 + 0.5) * span));
+                    data.push(span / 2.0 * DAY_SECONDS);
+                    for component in 0..3 {
+                        let from = offset - 1 + (sub * 3 + component) * ncf;
+                        data.extend(source[from..from + kept].iter().map(|c| c * scale));
+                    }
+                }
+            }
+            data.extend([
+                to_et(start),
+                span * DAY_SECONDS,
+                (2 + 3 * kept) as f64,
+                (count * nsub) as f64,
+            ]);
+            let finish = 3 * RECORD_BYTES / 8 + data.len();
+
+            summaries.extend([
+                to_et(start),
+                to_et(end),
+                pack(target, center),
+                pack(1, 2),
+                pack(begin as i32, finish as i32),
+            ]);
+        }
+        if segments.len() > (RECORD_BYTES / 8 - 3) / 5 {
+            return Err(Error::Config(
+                "Too many segments for one SPK summary record".to_string(),
+            ));
+        }
+
+        let mut file_record = vec![0u8; RECORD_BYTES];
+        put(&mut file_record, 0, b"DAF/SPK ");
+        put(&mut file_record, 8, &2i32.to_le_bytes());
+        put(&mut file_record, 12, &6i32.to_le_bytes());
+        put_text(&mut file_record, 16, 60, &label);
+        put(&mut file_record, 76, &2i32.to_le_bytes());
+        put(&mut file_record, 80, &2i32.to_le_bytes());
+        let free = 3 * RECORD_BYTES / 8 + data.len() + 1;
+        put(&mut file_record, 84, &(free as i32).to_le_bytes());
+        put(&mut file_record, 88, b"LTL-IEEE");
+        put(&mut file_record, 699, FTP_STRING);
+
+        let mut summary_record = vec![0.0; RECORD_BYTES / 8];
+        summary_record[2] = segments.len() as f64;
+        summary_record[3..3 + summaries.len()].copy_from_slice(&summaries);
+
+        let mut name_record = vec![b' '; RECORD_BYTES];
+        for k in 0..segments.len() {
+            put_text(&mut name_record, 40 * k, 40, &label);
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(&file_record)?;
+        write_doubles(&mut out, &summary_record)?;
+        out.write_all(&name_record)?;
+        write_doubles(&mut out, &data)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// First record and number of records covering the date range
+    fn records(&self, eph: &Ephemeris) -> Result<(usize, usize)> {
+        let metadata = eph.get_metadata();
+        let (start, end) = (self.start.jd, self.end.jd);
+        if end <= start || start < metadata.julian_start || end > metadata.julian_end {
+            return Err(Error::Config(format!(
+                "Subset [{}, {}] is not within the ephemeris range [{}, {}]",
+                start, end, metadata.julian_start, metadata.julian_end
+            )));
+        }
+        let interval = metadata.interval_days;
+        let first = ((start - metadata.julian_start) / interval).floor() as usize;
+        let last = ((end - metadata.julian_start) / interval).ceil() as usize;
+        Ok((first, last.min(eph.record_count()) - first))
+    }
+
+    /// Number of coefficients of each series of a body to write
+    fn kept_coefficients(
+        &self,
+        eph: &Ephemeris,
+        slot: usize,
+        first: usize,
+        count: usize,
+    ) -> Result<usize> {
+        let body = eph.get_bodies()[slot];
+        let (offset, ncf, nsub) = (
+            body.header_data[0] as usize,
+            body.header_data[1] as usize,
+            body.header_data[2] as usize,
+        );
+        let tolerance = match self.tolerance {
+            Some(tolerance) if slot <= LAST_POSITION_SLOT => tolerance,
+            _ => return Ok(ncf),
+        };
+
+        let mut kept = MIN_COEFFICIENTS.min(ncf);
+        for index in first..first + count {
+            let record = eph.read_record(index)?;
+            for k in 0..nsub * body.components {
+                let from = offset - 1 + k * ncf;
+                let series = &record[from..from + ncf];
+                let mut dropped = 0.0;
+                let mut needed = ncf;
+                while needed > kept && dropped + series[needed - 1].abs() <= tolerance {
+                    dropped += series[needed - 1].abs();
+                    needed -= 1;
+                }
+                kept = needed;
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Indices into the body list of the bodies to copy, in file order
+    fn slots(&self, eph: &Ephemeris) -> Result<Vec<usize>> {
+        let bodies = eph.get_bodies();
+        let has_data = |slot: usize| matches!(bodies[slot].header_data[..], [offset, ncf, nsub] if offset > 0 && ncf > 0 && nsub > 0);
+
+        let mut slots = Vec::new();
+        if self.bodies.is_empty() {
+            slots.extend((0..bodies.len()).filter(|&slot| has_data(slot)));
+        }
+        for name in &self.bodies {
+            let wanted: Vec<&str> =
+                if name.eq_ignore_ascii_case("Earth") || name.eq_ignore_ascii_case("Moon") {
+                    vec!["EarthMoon_barycenter", "Moon_geocentric"]
+                } else {
+                    vec![name.as_str()]
+                };
+            for wanted in wanted {
+                let slot = bodies
+                    .iter()
+                    .position(|b| b.matches(wanted))
+                    .ok_or_else(|| Error::UnknownBody {
+                        name: wanted.to_string(),
+                        available: bodies.iter().map(|b| b.name.clone()).collect(),
+                    })?;
+                if !has_data(slot) {
+                    return Err(Error::InactiveBody {
+                        name: bodies[slot].name.clone(),
+                    });
+                }
+                slots.push(slot);
+            }
+        }
+
+        slots.sort_unstable();
+        slots.dedup();
+        Ok(slots)
+    }
+}
+
+/// Contents of the two header records of a JPL binary ephemeris
+pub(crate) struct BinaryHeader {
+    /// Three title lines
+    pub(crate) titles: [String; 3],
+    /// Constant names and values in file order
+    pub(crate) constants: Vec<(String, f64)>,
+    /// Start and end Julian dates and record length in days
+    pub(crate) range: [f64; 3],
+    pub(crate) au: f64,
+    pub(crate) emrat: f64,
+    pub(crate) denum: i32,
+    /// Offset, coefficient count and sub-interval count of each body
+    pub(crate) pointers: [[i32; 3]; POINTERS],
+}
+
+impl BinaryHeader {
+    fn extra_names(&self) -> usize {
+        self.constants.len().saturating_sub(FIXED_CONSTANTS) * 6
+    }
+
+    /// Record length (doubles) for `ncoeff` coefficients per record, padded
+    /// when the header or the constant values would not fit
+    pub(crate) fn record_length(&self, ncoeff: usize) -> usize {
+        let header_len = HEADER_BYTES + self.extra_names() + 24;
+        ncoeff.max(header_len / 8 + 1).max(self.constants.len())
+    }
+
+    /// Encode both header records for records of `ncoeff` doubles
+    pub(crate) fn encode(&self, ncoeff: usize) -> Vec<u8> {
+        let extra = self.extra_names();
+        let mut header = vec![0u8; ncoeff * 8];
+        for (k, title) in self.titles.iter().enumerate() {
+            put_text(&mut header, 84 * k, 84, title);
+        }
+        for (k, (name, _)) in self.constants.iter().enumerate() {
+            let at = if k < FIXED_CONSTANTS {
+                252 + 6 * k
+            } else {
+                HEADER_BYTES + 6 * (k - FIXED_CONSTANTS)
+            };
+            put_text(&mut header, at, 6, name);
+        }
+        for (k, value) in self.range.iter().enumerate() {
+            put(&mut header, 2652 + 8 * k, &value.to_le_bytes());
+        }
+        put(
+            &mut header,
+            2676,
+            &(self.constants.len() as i32).to_le_bytes(),
+        );
+        put(&mut header, 2680, &self.au.to_le_bytes());
+        put(&mut header, 2688, &self.emrat.to_le_bytes());
+        put(&mut header, 2840, &self.denum.to_le_bytes());
+        for (slot, pointer) in self.pointers.iter().enumerate() {
+            let at = match slot {
+                0..=11 => 2696 + 12 * slot,
+                12 => 2844,
+                _ => HEADER_BYTES + extra + 12 * (slot - 13),
+            };
+            for (k, value) in pointer.iter().enumerate() {
+                put(&mut header, at + 4 * k, &value.to_le_bytes());
+            }
+        }
+
+        let mut values = vec![0u8; ncoeff * 8];
+        for (k, (_, value)) in self.constants.iter().enumerate() {
+            put(&mut values, 8 * k, &value.to_le_bytes());
+        }
+        header.extend(values);
+        header
+    }
+}
+
+fn put(buffer: &mut [u8], at: usize, bytes: &[u8]) {
+    buffer[at..at + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Write text into a fixed-width, blank-padded field
+fn put_text(buffer: &mut [u8], at: usize, width: usize, text: &str) {
+    let field = &mut buffer[at..at + width];
+    field.fill(b' ');
+    let bytes = text.as_bytes();
+    let length = bytes.len().min(width);
+    field[..length].copy_from_slice(&bytes[..length]);
+}
+
+/// Two DAF integers packed into the bytes of one double
+fn pack(a: i32, b: i32) -> f64 {
+    let mut word = [0u8; 8];
+    word[..4].copy_from_slice(&a.to_le_bytes());
+    word[4..].copy_from_slice(&b.to_le_bytes());
+    f64::from_le_bytes(word)
+}
+
+pub(crate) fn write_doubles(out: &mut impl Write, values: &[f64]) -> Result<()> {
+    for value in values {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::{model, write_binary};
+    use crate::spk::SpkKernel;
+
+    #[test]
+    fn test_subset_round_trip() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let source = dir.join(format!("rust_jpl_export_{}.bin", id));
+        let binary = dir.join(format!("rust_jpl_subset_{}.bin", id));
+        let spk = dir.join(format!("rust_jpl_subset_{}.bsp", id));
+        write_binary(&source);
+        let eph = Ephemeris::open(source.to_str().unwrap()).unwrap();
+
+        let jd = JulianDate::new(2451545.0);
+        let subset = Subset::new(JulianDate::new(2451540.0), JulianDate::new(2451550.0))
+            .with_bodies(&["Sun"]);
+        subset.write_binary(&eph, binary.to_str().unwrap()).unwrap();
+        subset.write_spk(&eph, spk.to_str().unwrap()).unwrap();
+
+        let trimmed = Ephemeris::open(binary.to_str().unwrap()).unwrap();
+        let kernel = SpkKernel::open(spk.to_str().unwrap()).unwrap();
+        for path in [&source, &binary, &spk] {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        assert_eq!(trimmed.get_metadata().ephemeris_number, 999);
+        assert_eq!(trimmed.get_constant("EMRAT"), Some(81.3));
+        assert_eq!(
+            trimmed.get_position("Sun", jd).unwrap(),
+            eph.get_position("Sun", jd).unwrap()
+        );
+        assert!(!trimmed.get_bodies()[9].active);
+
+        let (center, state) = kernel.state(10, jd).unwrap().unwrap();
+        assert_eq!(center, 0);
+        assert!((state.position.x - eph.au()).abs() < 1e-6);
+        assert!(Subset::new(jd, JulianDate::new(2451600.0))
+            .write_spk(&eph, "unused.bsp")
+            .is_err());
+    }
+
+    #[test]
+    fn test_moon_spk_reaches_barycenter() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_moon_{}.bsp", std::process::id()));
+        let path = path.to_str().unwrap();
+        let eph = model();
+        Subset::new(JulianDate::new(2451600.5), JulianDate::new(2451664.5))
+            .with_bodies(&["Moon_geocentric"])
+            .write_spk(&eph, path)
+            .unwrap();
+
+        let kernel = SpkKernel::open(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let targets: Vec<(i32, i32)> = kernel
+            .segments()
+            .iter()
+            .map(|s| (s.target, s.center))
+            .collect();
+        assert_eq!(targets, [(3, 0), (301, 3), (399, 3)]);
+
+        // Chaining 301 -> 3 -> 0 gives the barycentric Moon of the source
+        let jd = JulianDate::new(2451630.25);
+        let (_, moon) = kernel.state(301, jd).unwrap().unwrap();
+        let (center, emb) = kernel.state(3, jd).unwrap().unwrap();
+        assert_eq!(center, 0);
+        let chained = (moon.position + emb.position) * (1.0 / eph.au());
+        let expected = eph.get_position("Moon", jd).unwrap();
+        assert!((chained - expected).distance() * eph.au() < 1e-6);
+    }
+
+    #[test]
+    fn test_subset_tolerance() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let source = dir.join(format!("rust_jpl_full_{}.bin", id));
+        let compact = dir.join(format!("rust_jpl_compact_{}.bin", id));
+        write_binary(&source);
+        // Moon x series of the first sub-interval: 1000 + 10 T1 + 0.5 T2 + 0.2 T3 km
+        let mut bytes = std::fs::read(&source).unwrap();
+        for (k, c) in [1000.0f64, 10.0, 0.5, 0.2].iter().enumerate() {
+            let at = (2 * 398 + 8 + k) * 8;
+            bytes[at..at + 8].copy_from_slice(&c.to_le_bytes());
+        }
+        std::fs::write(&source, bytes).unwrap();
+        let eph = Ephemeris::open(source.to_str().unwrap()).unwrap();
+
+        Subset::new(JulianDate::new(2451536.5), JulianDate::new(2451568.5))
+            .with_bodies(&["Sun", "Moon_geocentric"])
+            .with_tolerance(0.3)
+            .write_binary(&eph, compact.to_str().unwrap())
+            .unwrap();
+        let trimmed = Ephemeris::open(compact.to_str().unwrap()).unwrap();
+        for path in [&source, &compact] {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        assert_eq!(trimmed.get_bodies()[9].header_data, [3, 3, 10]);
+        assert_eq!(trimmed.get_bodies()[10].header_data, [93, 2, 1]);
+        let deviations = crate::validation::compare(
+            &eph,
+            &trimmed,
+            &["Moon_geocentric", "Sun"],
+            2451536.5,
+            2451539.7,
+            0.1,
+        )
+        .unwrap();
+        assert_eq!(deviations[0].samples, 33);
+        assert!(deviations[0].max_km > 0.1 && deviations[0].max_km <= 0.2 + 1e-9);
+        assert_eq!(deviations[1].max_km, 0.0);
+    }
+}
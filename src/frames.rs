@@ -0,0 +1,207 @@
+//! Reference frame transformations: rotation matrices, precession and nutation
+//!
+//! Precession and the mean obliquity follow IAU 2006. The analytic nutation
+//! is a truncated IAU 1980 series, not IAU 2000A (see [`nutation`]).
+//!
+//! Angles are in radians unless stated otherwise. Rotations follow the
+//! convention of the IAU SOFA library: `rotation_x(a)` rotates the frame, not
+//! the vector, by `a` about the x axis.
+
+use crate::ephemeris::{Ephemeris, Position};
+use crate::time::JulianDate;
+use crate::Result;
+
+/// Julian date of the J2000.0 epoch
+pub const J2000: f64 = 2451545.0;
+
+/// Days per Julian century
+pub const DAYS_PER_CENTURY: f64 = 36525.0;
+
+/// Arcseconds to radians
+pub const ARCSEC: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+/// 3x3 rotation matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix3(pub [[f64; 3]; 3]);
+
+impl Matrix3 {
+    /// Identity matrix
+    pub fn identity() -> Self {
+        Matrix3([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Matrix product `self * other`
+    pub fn mul(&self, other: &Matrix3) -> Matrix3 {
+        let mut out = [[0.0; 3]; 3];
+        for (i, row) in out.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.0[i][k] * other.0[k][j]).sum();
+            }
+        }
+        Matrix3(out)
+    }
+
+    /// Transposed (for rotations, inverse) matrix
+    pub fn transpose(&self) -> Matrix3 {
+        let m = &self.0;
+        Matrix3([
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+        ])
+    }
+
+    /// Apply the matrix to a vector
+    pub fn apply(&self, v: &Position) -> Position {
+        let m = &self.0;
+        Position::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+/// Frame rotation about the x axis
+pub fn rotation_x(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    Matrix3([[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]])
+}
+
+/// Frame rotation about the y axis
+pub fn rotation_y(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    Matrix3([[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]])
+}
+
+/// Frame rotation about the z axis
+pub fn rotation_z(angle: f64) -> Matrix3 {
+    let (s, c) = angle.sin_cos();
+    Matrix3([[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]])
+}
+
+/// Julian centuries of TT since J2000.0
+pub fn centuries_since_j2000(jd_tt: f64) -> f64 {
+    (jd_tt - J2000) / DAYS_PER_CENTURY
+}
+
+/// Mean obliquity of the ecliptic (IAU 2006)
+pub fn mean_obliquity(jd_tt: f64) -> f64 {
+    let t = centuries_since_j2000(jd_tt);
+    (84381.406
+        + t * (-46.836769
+            + t * (-0.0001831 + t * (0.00200340 + t * (-0.000000576 + t * -0.0000000434)))))
+        * ARCSEC
+}
+
+/// Precession matrix from the J2000.0 mean equator and equinox (GCRS, frame
+/// bias neglected) to the mean equator and equinox of date (IAU 2006)
+pub fn precession_matrix(jd_tt: f64) -> Matrix3 {
+    let t = centuries_since_j2000(jd_tt);
+    let zeta = (2.650545
+        + t * (2306.083227
+            + t * (0.2988499 + t * (0.01801828 + t * (-0.000005971 + t * -0.0000003173)))))
+        * ARCSEC;
+    let z = (-2.650545
+        + t * (2306.077181
+            + t * (1.0927348 + t * (0.01826837 + t * (-0.000028596 + t * -0.0000002904)))))
+        * ARCSEC;
+    let theta = t
+        * (2004.191903
+            + t * (-0.4294934 + t * (-0.04182264 + t * (-0.000007089 + t * -0.0000001274))))
+        * ARCSEC;
+    rotation_z(-z)
+        .mul(&rotation_y(theta))
+        .mul(&rotation_z(-zeta))
+}
+
+/// Nutation in longitude and obliquity `(dpsi, deps)` from the 18 largest
+/// terms of the IAU 1980 series (Meeus, Astronomical Algorithms, table 22.A)
+///
+/// This is not the IAU 2000A model. The omitted terms amount to a few
+/// milliarcseconds, but the IAU 1980 theory itself departs from IAU 2000A by
+/// up to about 0.05" near J2000 and by more centuries away from it. Use
+/// [`nutation_angles`] to prefer the angles carried by the ephemeris.
+pub fn nutation(jd_tt: f64) -> (f64, f64) {
+    let t = centuries_since_j2000(jd_tt);
+    let deg = |x: f64| (x % 360.0).to_radians();
+    let d = deg(297.85036 + t * (445267.111480 + t * (-0.0019142 + t / 189474.0)));
+    let m = deg(357.52772 + t * (35999.050340 + t * (-0.0001603 - t / 300000.0)));
+    let mp = deg(134.96298 + t * (477198.867398 + t * (0.0086972 + t / 56250.0)));
+    let f = deg(93.27191 + t * (483202.017538 + t * (-0.0036825 + t / 327270.0)));
+    let om = deg(125.04452 + t * (-1934.136261 + t * (0.0020708 + t / 450000.0)));
+
+    let mut dpsi = 0.0;
+    let mut deps = 0.0;
+    for &[cd, cm, cmp, cf, com, s0, s1, c0, c1] in NUTATION_TERMS {
+        let arg = cd * d + cm * m + cmp * mp + cf * f + com * om;
+        dpsi += (s0 + s1 * t) * arg.sin();
+        deps += (c0 + c1 * t) * arg.cos();
+    }
+
+    (dpsi * 1e-4 * ARCSEC, deps * 1e-4 * ARCSEC)
+}
+
+/// Nutation angles `(dpsi, deps)` from the ephemeris when it carries them,
+/// otherwise from the analytic series
+pub fn nutation_angles(eph: &Ephemeris, jd_tt: JulianDate) -> Result<(f64, f64)> {
+    Ok(eph
+        .get_nutation(jd_tt)?
+        .unwrap_or_else(|| nutation(jd_tt.jd)))
+}
+
+/// Nutation matrix from the mean to the true equator and equinox of date
+pub fn nutation_matrix(mean_obliquity: f64, dpsi: f64, deps: f64) -> Matrix3 {
+    rotation_x(-(mean_obliquity + deps))
+        .mul(&rotation_z(-dpsi))
+        .mul(&rotation_x(mean_obliquity))
+}
+
+/// Multipliers of D, M, M', F, Omega and coefficients (0.0001") of the
+/// largest IAU 1980 nutation terms: sin(dpsi) constant and rate, cos(deps)
+/// constant and rate
+#[rustfmt::skip]
+const NUTATION_TERMS: &[[f64; 9]] = &[
+    [ 0.0,  0.0,  0.0,  0.0,  1.0, -171996.0, -174.2, 92025.0,  8.9],
+    [-2.0,  0.0,  0.0,  2.0,  2.0,  -13187.0,   -1.6,  5736.0, -3.1],
+    [ 0.0,  0.0,  0.0,  2.0,  2.0,   -2274.0,   -0.2,   977.0, -0.5],
+    [ 0.0,  0.0,  0.0,  0.0,  2.0,    2062.0,    0.2,  -895.0,  0.5],
+    [ 0.0,  1.0,  0.0,  0.0,  0.0,    1426.0,   -3.4,    54.0, -0.1],
+    [ 0.0,  0.0,  1.0,  0.0,  0.0,     712.0,    0.1,    -7.0,  0.0],
+    [-2.0,  1.0,  0.0,  2.0,  2.0,    -517.0,    1.2,   224.0, -0.6],
+    [ 0.0,  0.0,  0.0,  2.0,  1.0,    -386.0,   -0.4,   200.0,  0.0],
+    [ 0.0,  0.0,  1.0,  2.0,  2.0,    -301.0,    0.0,   129.0, -0.1],
+    [-2.0, -1.0,  0.0,  2.0,  2.0,     217.0,   -0.5,   -95.0,  0.3],
+    [-2.0,  0.0,  1.0,  0.0,  0.0,    -158.0,    0.0,     0.0,  0.0],
+    [-2.0,  0.0,  0.0,  2.0,  1.0,     129.0,    0.1,   -70.0,  0.0],
+    [ 0.0,  0.0, -1.0,  2.0,  2.0,     123.0,    0.0,   -53.0,  0.0],
+    [ 2.0,  0.0,  0.0,  0.0,  0.0,      63.0,    0.0,     0.0,  0.0],
+    [ 0.0,  0.0,  1.0,  0.0,  1.0,      63.0,    0.1,   -33.0,  0.0],
+    [ 2.0,  0.0, -1.0,  2.0,  2.0,     -59.0,    0.0,    26.0,  0.0],
+    [ 0.0,  0.0, -1.0,  0.0,  1.0,     -58.0,   -0.1,    32.0,  0.0],
+    [ 0.0,  0.0,  1.0,  2.0,  1.0,     -51.0,    0.0,    27.0,  0.0],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precession_and_nutation() {
+        // No precession at the reference epoch
+        let p = precession_matrix(J2000);
+        let v = p.apply(&Position::new(1.0, 0.0, 0.0));
+        assert!((v.x - 1.0).abs() < 1e-12);
+
+        // Rotations are orthogonal
+        let p = precession_matrix(J2000 + 3652.5);
+        let i = p.mul(&p.transpose());
+        assert!((i.0[0][0] - 1.0).abs() < 1e-12 && i.0[0][1].abs() < 1e-12);
+
+        // Meeus, Astronomical Algorithms, example 22.a (full IAU 1980 series);
+        // the truncation costs a few milliarcseconds
+        let (dpsi, deps) = nutation(2446895.5);
+        assert!((dpsi / ARCSEC + 3.788).abs() < 0.005);
+        assert!((deps / ARCSEC - 9.443).abs() < 0.005);
+    }
+}
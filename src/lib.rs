@@ -6,9 +6,26 @@
 //! ## Features
 //!
 //! - Planetary Positions: Accurate positions of celestial bodies for a given Julian date
-//! - Ephemeris Data: Utilization of DE441 file for precise planetary information
+//! - Ephemeris Data: DE200 through DE441 binary files, detected from their headers
+//! - ASCII Ephemerides: Header and ascp data file parsing and conversion to binary
 //! - Time Conversions: Conversion between Julian dates and calendar dates
 //! - Lunar Ephemerides: Specific data for accurate moon-related calculations
+//! - Topocentric Positions: Altitude/azimuth for observers on the Earth's surface
+//! - Rise, Set and Transit: Event times for any body from an observer location
+//! - Lunar Phases: Illuminated fraction and new/full moon and quarter instants
+//! - Seasons: Equinoxes, solstices and solar terms in TDB and UTC
+//! - Eclipses: Solar and lunar eclipse prediction with local circumstances
+//! - Conjunctions: Planetary conjunctions, oppositions and greatest elongations
+//! - Astrology: Tropical and sidereal zodiac, house cusps and aspects
+//! - Orbital Elements: Osculating Keplerian elements to and from state vectors
+//! - Builder: Ephemerides from paths, readers or bytes with environment overrides
+//! - Ephemeris Sets: Several ephemerides queried in priority order with fallback
+//! - Export: Date range and body subsets written as JPL binary or SPK files
+//! - SPK Kernels: Extra bodies such as the sb441-n16 asteroids from SPICE SPK files
+//! - Small Bodies: Two-body propagation of MPC comet and asteroid orbits
+//! - Stations: Retrograde and direct stations and retrograde periods of planets
+//! - Event Search: Roots, extrema and threshold windows of user-defined functions
+//! - Validation: Comparison against JPL testpo test points within JPL's tolerance
 //! - Solar System Dynamics: Detailed data for understanding solar system dynamics
 //!
 //! ## Example
@@ -28,11 +45,33 @@
 //! # Ok::<(), rust_jpl::Error>(())
 //! ```
 
+pub mod apparent;
+pub mod ascii;
+pub mod astrology;
+pub mod builder;
 pub mod config;
+pub mod conjunctions;
+pub mod eclipse;
+pub mod elements;
 pub mod ephemeris;
+pub mod ephemeris_set;
+pub mod export;
+pub mod frames;
+pub mod lunar;
+pub mod observer;
+pub mod riseset;
+pub mod search;
+pub mod seasons;
+pub mod smallbody;
+pub mod spk;
+pub mod stations;
 pub mod time;
+pub mod validation;
 
-pub use ephemeris::{Ephemeris, EphemerisMetadata, Position, SpaceObject};
+pub use builder::EphemerisBuilder;
+pub use ephemeris::{Discontinuity, Ephemeris, EphemerisMetadata, Position, SpaceObject, State};
+pub use ephemeris_set::EphemerisSet;
+pub use observer::{Ellipsoid, HorizontalCoordinates, Observer, Refraction};
 pub use time::{CalendarDate, JulianDate};
 
 /// Error types for the library
@@ -46,6 +85,98 @@ pub enum Error {
     InvalidDate(String),
     /// Ephemeris data error
     Ephemeris(String),
+    /// Julian date outside the range covered by the ephemeris
+    DateOutOfRange {
+        /// Requested Julian date (TDB)
+        jd: f64,
+        /// First Julian date covered
+        start: f64,
+        /// Last Julian date covered
+        end: f64,
+    },
+    /// Body name not found in the ephemeris
+    UnknownBody {
+        /// Requested name
+        name: String,
+        /// Bodies of the ephemeris
+        available: Vec<String>,
+    },
+    /// Body known to the ephemeris but inactive or without coefficients
+    InactiveBody {
+        /// Body name
+        name: String,
+    },
+    /// Data record missing, or with wrong dates or non-finite coefficients
+    CorruptRecord {
+        /// Record index (0-based, not counting the header records)
+        index: usize,
+        /// What is wrong with the record
+        reason: String,
+    },
+    /// Binary header inconsistent with itself or with the file size
+    CorruptHeader {
+        /// Header field at fault, e.g. `NCON`
+        field: String,
+        /// What is wrong with it
+        reason: String,
+    },
+    /// SHA-256 of the binary ephemeris differs from the expected digest
+    ChecksumMismatch {
+        /// Expected digest (lowercase hex)
+        expected: String,
+        /// Digest of the file (lowercase hex)
+        actual: String,
+    },
+    /// Malformed line of a text file
+    Parse {
+        /// Kind of file being read
+        kind: FileKind,
+        /// Line number (1-based)
+        line: usize,
+        /// `GROUP` of an ASCII header the line belongs to, otherwise empty
+        group: String,
+        /// What could not be parsed
+        message: String,
+    },
+    /// Error reading a particular file
+    File {
+        /// Path of the file
+        path: String,
+        /// Underlying error
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attach the path of the file being read, unless the error already
+    /// names one
+    pub(crate) fn in_file(self, path: &str) -> Self {
+        match self {
+            Error::File { .. } => self,
+            other => Error::File {
+                path: path.to_string(),
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// The error without file context
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::{Ephemeris, Error};
+    /// match Ephemeris::open("de441.bin") {
+    ///     Ok(eph) => println!("DE{}", eph.get_metadata().ephemeris_number),
+    ///     Err(err) if matches!(err.root(), Error::Io(_)) => eprintln!("cannot read: {}", err),
+    ///     Err(err) => eprintln!("damaged file: {}", err),
+    /// }
+    /// ```
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::File { source, .. } => source.root(),
+            other => other,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
@@ -55,11 +186,64 @@ impl std::fmt::Display for Error {
             Error::Io(err) => write!(f, "I/O error: {}", err),
             Error::InvalidDate(msg) => write!(f, "Invalid date: {}", msg),
             Error::Ephemeris(msg) => write!(f, "Ephemeris error: {}", msg),
+            Error::DateOutOfRange { jd, start, end } => write!(
+                f,
+                "Date out of range: Julian date {} is outside valid range [{}, {}]",
+                jd, start, end
+            ),
+            Error::UnknownBody { name, available } => write!(
+                f,
+                "Unknown body: '{}' not found. Available bodies: {}",
+                name,
+                available.join(", ")
+            ),
+            Error::InactiveBody { name } => {
+                write!(
+                    f,
+                    "Inactive body: '{}' is not active in this ephemeris",
+                    name
+                )
+            }
+            Error::CorruptRecord { index, reason } => {
+                write!(f, "Corrupt record: record {} {}", index, reason)
+            }
+            Error::CorruptHeader { field, reason } => {
+                write!(f, "Corrupt header: {} {}", field, reason)
+            }
+            Error::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Checksum mismatch: SHA-256 is {} but {} was expected",
+                actual, expected
+            ),
+            Error::Parse {
+                kind,
+                line,
+                group,
+                message,
+            } => {
+                if group.is_empty() {
+                    write!(f, "Parse error in {} at line {}: {}", kind, line, message)
+                } else {
+                    write!(
+                        f,
+                        "Parse error in {} at line {} (group {}): {}",
+                        kind, line, group, message
+                    )
+                }
+            }
+            Error::File { path, source } => write!(f, "{}: {}", path, source),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
@@ -68,3 +252,24 @@ impl From<std::io::Error> for Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Kind of text file named by [`Error::Parse`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// ASCII header of a JPL ephemeris (`header.441`)
+    AsciiHeader,
+    /// ASCII data file of a JPL ephemeris (`ascp01950.441`)
+    AsciiData,
+    /// JPL test point file (`testpo.441`)
+    TestPoints,
+}
+
+impl std::fmt::Display for FileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FileKind::AsciiHeader => "ASCII header",
+            FileKind::AsciiData => "ASCII data",
+            FileKind::TestPoints => "test points",
+        })
+    }
+}
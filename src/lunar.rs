@@ -0,0 +1,455 @@
+//! Lunar phases: illuminated fraction, phase angle and the instants of new
+//! moon, first quarter, full moon and last quarter
+//!
+//! Also the Moon's nodes and apsides: mean values from analytic series,
+//! true osculating values from the geocentric lunar state, and searches for
+//! perigee, apogee and node passages.
+
+use crate::apparent::{self, ASTRONOMICAL_UNIT};
+use crate::ephemeris::{Ephemeris, Position};
+use crate::frames;
+use crate::search::{EventFinder, EventKind};
+use crate::time::JulianDate;
+use crate::Result;
+
+/// Sampling step of the phase search (days)
+const PHASE_STEP: f64 = 1.0;
+
+/// Precision of the returned phase times (days, about 0.01 s)
+const PHASE_TOLERANCE: f64 = 1e-7;
+
+/// Sampling step of the apsis and node searches (days)
+const ORBIT_STEP: f64 = 1.0;
+
+/// Gravitational parameter of the Earth-Moon system (AU^3/day^2), used
+/// when the header does not provide GMB
+const EARTH_MOON_GM: f64 = 8.997011603631609e-10;
+
+/// Principal lunar phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoonPhase {
+    /// Sun and Moon at the same apparent ecliptic longitude
+    NewMoon,
+    /// Moon 90 degrees east of the Sun
+    FirstQuarter,
+    /// Moon 180 degrees from the Sun
+    FullMoon,
+    /// Moon 270 degrees east of the Sun
+    LastQuarter,
+}
+
+impl MoonPhase {
+    fn from_quarter(quarter: i64) -> Self {
+        match quarter.rem_euclid(4) {
+            0 => MoonPhase::NewMoon,
+            1 => MoonPhase::FirstQuarter,
+            2 => MoonPhase::FullMoon,
+            _ => MoonPhase::LastQuarter,
+        }
+    }
+}
+
+/// Instant of a principal lunar phase
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LunarPhaseEvent {
+    /// Phase reached
+    pub phase: MoonPhase,
+    /// Time of the phase (TDB)
+    pub jd: JulianDate,
+}
+
+/// Perigee or apogee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApsisKind {
+    /// Closest approach to the Earth
+    Perigee,
+    /// Greatest distance from the Earth
+    Apogee,
+}
+
+/// Passage of the Moon through perigee or apogee
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LunarApsis {
+    /// Perigee or apogee
+    pub kind: ApsisKind,
+    /// Time of the passage (TDB)
+    pub jd: JulianDate,
+    /// Geocentric distance of the Moon (km)
+    pub distance: f64,
+}
+
+/// Ascending or descending node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The Moon crosses the ecliptic northwards
+    Ascending,
+    /// The Moon crosses the ecliptic southwards
+    Descending,
+}
+
+/// Passage of the Moon through one of its nodes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeCrossing {
+    /// Ascending or descending node
+    pub kind: NodeKind,
+    /// Time of the crossing (TDB)
+    pub jd: JulianDate,
+    /// Apparent ecliptic longitude of the Moon at the crossing (degrees)
+    pub longitude: f64,
+}
+
+/// Difference between the apparent geocentric ecliptic longitudes of the
+/// Moon and the Sun (degrees, 0-360)
+///
+/// 0 is new moon, 90 first quarter, 180 full moon and 270 last quarter.
+pub fn elongation_longitude(eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+    let moon = apparent::ecliptic_of_date(eph, "Moon", jd)?;
+    let sun = apparent::ecliptic_of_date(eph, "Sun", jd)?;
+    Ok((moon.longitude - sun.longitude).rem_euclid(360.0))
+}
+
+/// Lunar phase angle: the Sun-Moon-Earth angle (degrees, 0 at full moon,
+/// 180 at new moon)
+pub fn phase_angle(eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+    let moon = apparent::apparent(eph, "Moon", jd)?;
+    let sun = apparent::apparent(eph, "Sun", jd)?;
+    let moon_to_earth = -moon;
+    let moon_to_sun = sun - moon;
+    let cos_angle = moon_to_earth.unit().dot(&moon_to_sun.unit());
+    Ok(cos_angle.clamp(-1.0, 1.0).acos().to_degrees())
+}
+
+/// Illuminated fraction of the lunar disk (0 at new moon, 1 at full moon)
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::lunar::illuminated_fraction;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let jd = JulianDate::from_calendar(2024, 3, 25, 7, 0, 0.0)?;
+/// println!("Illuminated: {:.1}%", 100.0 * illuminated_fraction(&eph, jd)?);
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn illuminated_fraction(eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+    Ok((1.0 + phase_angle(eph, jd)?.to_radians().cos()) / 2.0)
+}
+
+/// Find new moons, first quarters, full moons and last quarters between
+/// two dates
+///
+/// # Arguments
+/// * `eph` - Loaded ephemeris
+/// * `start` - Start of the search window (TDB)
+/// * `end` - End of the search window (TDB)
+///
+/// # Returns
+/// Phase events sorted by time
+pub fn find_phases(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<LunarPhaseEvent>> {
+    // Distance from the nearest quarter, wrapping halfway between quarters;
+    // only the upward crossings are phase instants.
+    let offset = |jd: JulianDate| -> Result<f64> {
+        let elongation = elongation_longitude(eph, jd)?;
+        Ok((elongation + 45.0).rem_euclid(90.0) - 45.0)
+    };
+
+    let finder = EventFinder::for_ephemeris(eph)
+        .with_step(PHASE_STEP)
+        .with_tolerance(PHASE_TOLERANCE);
+    let mut events = Vec::new();
+    for root in finder.roots(offset, start, end)? {
+        if root.kind != EventKind::Rising {
+            continue;
+        }
+        let jd = root.jd;
+        let quarter = (elongation_longitude(eph, jd)? / 90.0).round() as i64;
+        events.push(LunarPhaseEvent {
+            phase: MoonPhase::from_quarter(quarter),
+            jd,
+        });
+    }
+
+    Ok(events)
+}
+
+/// Longitude of the Moon's mean ascending node, referred to the mean
+/// equinox of date (degrees, 0-360)
+pub fn mean_node(jd: JulianDate) -> f64 {
+    let t = frames::centuries_since_j2000(jd.jd);
+    (125.0445479 + t * (-1934.1362891 + t * (0.0020754 + t * (1.0 / 467441.0 - t / 60616000.0))))
+        .rem_euclid(360.0)
+}
+
+/// Longitude of the Moon's mean perigee, referred to the mean equinox of
+/// date (degrees, 0-360); the mean apogee is 180 degrees away
+pub fn mean_perigee(jd: JulianDate) -> f64 {
+    let t = frames::centuries_since_j2000(jd.jd);
+    (83.3532465 + t * (4069.0137287 + t * (-0.01032 + t * (-1.0 / 80053.0 + t / 18999000.0))))
+        .rem_euclid(360.0)
+}
+
+/// Longitude of the Moon's true (osculating) ascending node, referred to
+/// the true ecliptic and equinox of date (degrees, 0-360)
+pub fn true_node(eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+    let (momentum, _) = osculating_orbit(eph, jd)?;
+    Ok(momentum.x.atan2(-momentum.y).to_degrees().rem_euclid(360.0))
+}
+
+/// Ecliptic longitude of the Moon's osculating perigee, referred to the
+/// true ecliptic and equinox of date (degrees, 0-360)
+///
+/// The osculating orbit is strongly perturbed by the Sun, so this point
+/// can differ from the actual perigee by tens of degrees.
+pub fn true_perigee(eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+    let (_, eccentricity) = osculating_orbit(eph, jd)?;
+    Ok(eccentricity
+        .y
+        .atan2(eccentricity.x)
+        .to_degrees()
+        .rem_euclid(360.0))
+}
+
+/// Find the Moon's perigee and apogee passages between two dates
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::lunar::find_apsides;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2024, 3, 1, 0, 0, 0.0)?;
+/// for apsis in find_apsides(&eph, start, end)? {
+///     println!("{:?} at JD {:.4}: {:.0} km", apsis.kind, apsis.jd.as_f64(), apsis.distance);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_apsides(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<LunarApsis>> {
+    let au = eph.get_constant("AU").unwrap_or(ASTRONOMICAL_UNIT);
+    let distance = |jd: JulianDate| -> Result<f64> {
+        Ok(eph.get_position("Moon_geocentric", jd)?.distance() * au)
+    };
+
+    let finder = EventFinder::for_ephemeris(eph)
+        .with_step(ORBIT_STEP)
+        .with_tolerance(PHASE_TOLERANCE);
+    Ok(finder
+        .extrema(distance, start, end)?
+        .into_iter()
+        .map(|extremum| LunarApsis {
+            kind: if extremum.kind == EventKind::Minimum {
+                ApsisKind::Perigee
+            } else {
+                ApsisKind::Apogee
+            },
+            jd: extremum.jd,
+            distance: extremum.value,
+        })
+        .collect())
+}
+
+/// Find the Moon's passages through the ecliptic of date between two dates
+pub fn find_node_crossings(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<NodeCrossing>> {
+    let latitude = |jd: JulianDate| -> Result<f64> {
+        Ok(apparent::ecliptic_of_date(eph, "Moon", jd)?.latitude)
+    };
+
+    let finder = EventFinder::for_ephemeris(eph)
+        .with_step(ORBIT_STEP)
+        .with_tolerance(PHASE_TOLERANCE);
+    let mut crossings = Vec::new();
+    for root in finder.roots(latitude, start, end)? {
+        crossings.push(NodeCrossing {
+            kind: if root.kind == EventKind::Rising {
+                NodeKind::Ascending
+            } else {
+                NodeKind::Descending
+            },
+            jd: root.jd,
+            longitude: apparent::ecliptic_of_date(eph, "Moon", root.jd)?.longitude,
+        });
+    }
+    Ok(crossings)
+}
+
+/// Angular momentum and eccentricity vectors of the Moon's geocentric
+/// two-body orbit, in the true ecliptic and equinox of date
+fn osculating_orbit(eph: &Ephemeris, jd: JulianDate) -> Result<(Position, Position)> {
+    let gm = eph.get_constant("GMB").unwrap_or(EARTH_MOON_GM);
+    let state = eph.get_state("Moon_geocentric", jd)?;
+    let rotation = apparent::ecliptic_of_date_matrix(eph, jd)?;
+    let r = rotation.apply(&state.position);
+    let v = rotation.apply(&state.velocity);
+
+    let momentum = r.cross(&v);
+    let eccentricity = v.cross(&momentum) * (1.0 / gm) - r.unit();
+    Ok((momentum, eccentricity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::{model, MODEL_MOON};
+
+    /// One second in days
+    const SECOND: f64 = 1.0 / 86400.0;
+
+    fn quarter(phase: MoonPhase) -> i64 {
+        match phase {
+            MoonPhase::NewMoon => 0,
+            MoonPhase::FirstQuarter => 1,
+            MoonPhase::FullMoon => 2,
+            MoonPhase::LastQuarter => 3,
+        }
+    }
+
+    #[test]
+    fn test_find_phases() {
+        let eph = model();
+        let (start, end) = (JulianDate::new(2451550.0), JulianDate::new(2451610.0));
+        let phases = find_phases(&eph, start, end).unwrap();
+        assert_eq!(phases.len(), 9);
+        assert_eq!(phases[0].phase, MoonPhase::NewMoon);
+        for pair in phases.windows(2) {
+            assert_eq!((quarter(pair[0].phase) + 1) % 4, quarter(pair[1].phase));
+        }
+
+        // The elongation passes through the quarter within a second of each
+        // returned instant
+        for event in &phases {
+            let target = 90.0 * quarter(event.phase) as f64;
+            let offset = |dt: f64| {
+                let jd = JulianDate::new(event.jd.jd + dt);
+                (elongation_longitude(&eph, jd).unwrap() - target + 180.0).rem_euclid(360.0) - 180.0
+            };
+            assert!(offset(-SECOND) < 0.0 && offset(SECOND) > 0.0);
+        }
+
+        let new_moons: Vec<f64> = phases
+            .iter()
+            .filter(|e| e.phase == MoonPhase::NewMoon)
+            .map(|e| e.jd.jd)
+            .collect();
+        let synodic = 1.0 / (1.0 / 27.321661 - 1.0 / 365.25636);
+        assert!((new_moons[1] - new_moons[0] - synodic).abs() < 0.6);
+        let full = phases
+            .iter()
+            .find(|e| e.phase == MoonPhase::FullMoon)
+            .unwrap();
+        assert!(illuminated_fraction(&eph, full.jd).unwrap() > 0.99);
+        assert!(illuminated_fraction(&eph, JulianDate::new(new_moons[0])).unwrap() < 0.01);
+    }
+
+    #[test]
+    fn test_find_apsides() {
+        let eph = model();
+        let (start, end) = (JulianDate::new(2451550.0), JulianDate::new(2451600.0));
+        let apsides = find_apsides(&eph, start, end).unwrap();
+        assert_eq!(apsides.len(), 3);
+
+        // Perigee and apogee of the model orbit fall at mean anomalies 0 and 180
+        for apsis in &apsides {
+            let (mean, distance) = match apsis.kind {
+                ApsisKind::Perigee => (0.0, MODEL_MOON.a * (1.0 - MODEL_MOON.e)),
+                ApsisKind::Apogee => (180.0, MODEL_MOON.a * (1.0 + MODEL_MOON.e)),
+            };
+            let expected = MODEL_MOON.passage(apsis.jd.jd - 1.0, mean);
+            assert!((apsis.jd.jd - expected).abs() < SECOND);
+            assert!((apsis.distance - distance).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_find_node_crossings() {
+        let eph = model();
+        let (start, end) = (JulianDate::new(2451540.0), JulianDate::new(2451570.0));
+        let crossings = find_node_crossings(&eph, start, end).unwrap();
+        let kinds: Vec<NodeKind> = crossings.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, [NodeKind::Descending, NodeKind::Ascending]);
+
+        // Nodes of the model orbit, where the argument of latitude is 0 or 180
+        for crossing in &crossings {
+            let latitude = match crossing.kind {
+                NodeKind::Ascending => 0.0,
+                NodeKind::Descending => 180.0,
+            };
+            let nu = (latitude - MODEL_MOON.perihelion).to_radians();
+            let mean = crate::elements::mean_from_true(nu, MODEL_MOON.e).to_degrees();
+            let expected = MODEL_MOON.passage(crossing.jd.jd - 1.0, mean);
+            assert!((crossing.jd.jd - expected).abs() < SECOND);
+
+            let node = (MODEL_MOON.node + latitude).rem_euclid(360.0);
+            assert!((crossing.longitude - node).abs() < 0.01);
+        }
+    }
+
+    /// General precession in longitude since J2000 (degrees)
+    fn precession(jd: f64) -> f64 {
+        50.29 / 3600.0 * (jd - 2451545.0) / 365.25
+    }
+
+    #[test]
+    fn test_mean_node_and_perigee() {
+        // Meeus, Astronomical Algorithms, example 22.a: 1987 April 10
+        assert!((mean_node(JulianDate::new(2446895.5)) - 11.2531).abs() < 1e-4);
+
+        // Example 47.a, 1992 April 12: node L' - F and perigee L' - M' from the
+        // mean longitude 134.290182, argument of latitude 219.889721 and
+        // anomaly 5.150833 of the Moon
+        let jd = JulianDate::new(2448724.5);
+        assert!((mean_node(jd) - (134.290182 - 219.889721 + 360.0)).abs() < 1e-3);
+        assert!((mean_perigee(jd) - (134.290182 - 5.150833)).abs() < 1e-3);
+
+        // The node regresses once in 6798.38 days relative to the equinox;
+        // the perigee advances once in 3232.6 days relative to the stars
+        let offset = |a: f64, b: f64| (a - b + 180.0).rem_euclid(360.0) - 180.0;
+        let later = JulianDate::new(jd.jd + 6798.38);
+        assert!(offset(mean_node(later), mean_node(jd)).abs() < 1e-3);
+        let later = JulianDate::new(jd.jd + 3232.6);
+        let advance = offset(mean_perigee(later), mean_perigee(jd));
+        assert!((advance - precession(2451545.0 + 3232.6)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_nodes() {
+        let eph = model();
+        for jd in [2451545.0, 2451700.0, 2452000.0] {
+            let node = true_node(&eph, JulianDate::new(jd)).unwrap();
+            assert!((node - MODEL_MOON.node - precession(jd)).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_true_perigee() {
+        // The perigee of the model orbit, at argument of latitude omega,
+        // projected on the ecliptic
+        let (i, omega) = (
+            MODEL_MOON.i.to_radians(),
+            MODEL_MOON.perihelion.to_radians(),
+        );
+        let perigee = MODEL_MOON.node + (i.cos() * omega.sin()).atan2(omega.cos()).to_degrees();
+        assert!((perigee.rem_euclid(360.0) - 83.305).abs() < 1e-3);
+
+        let eph = model();
+        for jd in [2451545.0, 2451700.0, 2452000.0] {
+            let longitude = true_perigee(&eph, JulianDate::new(jd)).unwrap();
+            let expected = (perigee + precession(jd)).rem_euclid(360.0);
+            assert!(
+                (longitude - expected).abs() < 0.01,
+                "{} {}",
+                longitude,
+                expected
+            );
+        }
+    }
+}
@@ -1,6 +1,288 @@
+use rust_jpl::ascii;
+use rust_jpl::export::{ExportFormat, Subset};
+use rust_jpl::validation;
 use rust_jpl::{Ephemeris, JulianDate};
 
+const USAGE: &str = "Usage:
+  rust-jpl                 Print metadata and sample positions from config.toml
+  rust-jpl export [options] <output>
+      --config <file>      Configuration to load (default: config.toml)
+      --ephemeris <file>   Open a binary ephemeris instead of a configuration
+      --from <date>        Start date, YYYY-MM-DD or Julian date (required)
+      --to <date>          End date, YYYY-MM-DD or Julian date (required)
+      --bodies <list>      Comma-separated bodies (default: all)
+      --format <jpl|spk>   Output format (default: spk for .bsp, else jpl)
+      --tolerance <km>     Drop Chebyshev terms of the Sun, Moon and planets
+                           while positions stay within this distance
+  rust-jpl convert --header <file> --output <file> <data files...>
+      Assemble JPL ASCII files (header.441, ascp*.441) into a binary
+      ephemeris; data files are taken in chronological order
+  rust-jpl verify [options] <testpo file>
+      --config <file>      Configuration to load (default: config.toml)
+      --ephemeris <file>   Open a binary ephemeris instead of a configuration
+      --tolerance <value>  Largest accepted deviation (default: 1e-13)
+  rust-jpl compare [options] <binary ephemeris>
+      Largest position differences against a reference ephemeris
+      --config <file>      Reference configuration (default: config.toml)
+      --ephemeris <file>   Reference binary ephemeris instead of a configuration
+      --from <date>        Start date (default: start of the compared file)
+      --to <date>          End date (default: end of the compared file)
+      --step <days>        Days between compared dates (default: 0.5)
+      --bodies <list>      Comma-separated bodies (default: Sun, Moon, planets)";
+
+/// Bodies compared by default
+const COMPARED_BODIES: [&str; 11] = [
+    "Sun", "Mercury", "Venus", "Earth", "Moon", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune",
+    "Pluto",
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("export") => export(&args[1..]),
+        Some("convert") => convert(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some("compare") => compare(&args[1..]),
+        Some("help" | "-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(other) => {
+            eprintln!("Unknown command '{}'\n\n{}", other, USAGE);
+            std::process::exit(2);
+        }
+        None => demo(),
+    }
+}
+
+/// Write a subset of an ephemeris to a new file
+fn export(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = "config.toml".to_string();
+    let mut ephemeris = None;
+    let (mut from, mut to, mut bodies, mut format, mut output) = (None, None, None, None, None);
+    let mut tolerance = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--config" => config = value()?,
+            "--ephemeris" => ephemeris = Some(value()?),
+            "--from" => from = Some(parse_date(&value()?)?),
+            "--to" => to = Some(parse_date(&value()?)?),
+            "--bodies" => bodies = Some(value()?),
+            "--format" => {
+                format = Some(match value()?.to_ascii_lowercase().as_str() {
+                    "jpl" | "binary" => ExportFormat::JplBinary,
+                    "spk" | "bsp" => ExportFormat::Spk,
+                    other => return Err(format!("Unknown format '{}'", other).into()),
+                })
+            }
+            "--tolerance" => {
+                let text = value()?;
+                tolerance = Some(
+                    text.parse()
+                        .map_err(|_| format!("Invalid tolerance '{}'", text))?,
+                );
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg).into()),
+            _ => output = Some(arg.clone()),
+        }
+    }
+
+    let (from, to, output) = match (from, to, output) {
+        (Some(from), Some(to), Some(output)) => (from, to, output),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let format = format.unwrap_or(if output.ends_with(".bsp") {
+        ExportFormat::Spk
+    } else {
+        ExportFormat::JplBinary
+    });
+
+    let eph = match ephemeris {
+        Some(path) => Ephemeris::open(&path)?,
+        None => Ephemeris::new(&config)?,
+    };
+    let mut subset = Subset::new(from, to);
+    if let Some(bodies) = &bodies {
+        let names: Vec<&str> = bodies.split(',').map(str::trim).collect();
+        subset = subset.with_bodies(&names);
+    }
+    if let Some(km) = tolerance {
+        subset = subset.with_tolerance(km);
+    }
+    subset.write(&eph, &output, format)?;
+    println!("Wrote {:?} subset to {}", format, output);
+    Ok(())
+}
+
+/// Assemble ASCII ephemeris files into a binary ephemeris
+fn convert(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut header, mut output, mut data) = (None, None, Vec::new());
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--header" => header = Some(value()?),
+            "--output" => output = Some(value()?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg).into()),
+            _ => data.push(arg.as_str()),
+        }
+    }
+
+    let (header, output) = match (header, output) {
+        (Some(header), Some(output)) if !data.is_empty() => (header, output),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let records = ascii::convert(&header, &data, &output)?;
+    println!("Wrote {} records to {}", records, output);
+    Ok(())
+}
+
+/// Compare an ephemeris against a JPL test-point file
+fn verify(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = "config.toml".to_string();
+    let (mut ephemeris, mut testpo) = (None, None);
+    let mut tolerance = validation::TOLERANCE;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--config" => config = value()?,
+            "--ephemeris" => ephemeris = Some(value()?),
+            "--tolerance" => {
+                let text = value()?;
+                tolerance = text
+                    .parse()
+                    .map_err(|_| format!("Invalid tolerance '{}'", text))?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg).into()),
+            _ => testpo = Some(arg.clone()),
+        }
+    }
+
+    let testpo = match testpo {
+        Some(testpo) => testpo,
+        None => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let eph = match ephemeris {
+        Some(path) => Ephemeris::open(&path)?,
+        None => Ephemeris::new(&config)?,
+    };
+    let report = validation::verify_file(&eph, &testpo, tolerance)?;
+    println!("{}", report);
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Compare a binary ephemeris, such as a trimmed subset, against a reference
+fn compare(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = "config.toml".to_string();
+    let (mut ephemeris, mut candidate, mut from, mut to, mut bodies) =
+        (None, None, None, None, None);
+    let mut step = 0.5;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--config" => config = value()?,
+            "--ephemeris" => ephemeris = Some(value()?),
+            "--from" => from = Some(parse_date(&value()?)?),
+            "--to" => to = Some(parse_date(&value()?)?),
+            "--bodies" => bodies = Some(value()?),
+            "--step" => {
+                let text = value()?;
+                step = text
+                    .parse()
+                    .map_err(|_| format!("Invalid step '{}'", text))?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg).into()),
+            _ => candidate = Some(arg.clone()),
+        }
+    }
+
+    let candidate = match candidate {
+        Some(path) => Ephemeris::open(&path)?,
+        None => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let reference = match ephemeris {
+        Some(path) => Ephemeris::open(&path)?,
+        None => Ephemeris::new(&config)?,
+    };
+    let (start, end) = candidate.get_date_range();
+    let start = from.map_or(start, |jd| jd.as_f64());
+    let end = to.map_or(end, |jd| jd.as_f64());
+    let names: Vec<&str> = match &bodies {
+        Some(bodies) => bodies.split(',').map(str::trim).collect(),
+        None => COMPARED_BODIES.to_vec(),
+    };
+
+    let comparisons = validation::compare(&reference, &candidate, &names, start, end, step)?;
+    println!(
+        "{:<12} {:>8} {:>12} {:>14}",
+        "body", "dates", "max km", "at JD"
+    );
+    for body in comparisons {
+        println!(
+            "{:<12} {:>8} {:>12.6} {:>14.4}",
+            body.name, body.samples, body.max_km, body.worst_jd
+        );
+    }
+    Ok(())
+}
+
+/// Parse `YYYY-MM-DD` (0h TDB) or a Julian date
+fn parse_date(text: &str) -> Result<JulianDate, Box<dyn std::error::Error>> {
+    if let Ok(jd) = text.parse::<f64>() {
+        return Ok(JulianDate::new(jd));
+    }
+    let parts: Vec<i32> = text
+        .splitn(3, '-')
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", text))?;
+    match parts[..] {
+        [year, month, day] => Ok(JulianDate::from_calendar(year, month, day, 0, 0, 0.0)?),
+        _ => Err(format!("Invalid date '{}', expected YYYY-MM-DD", text).into()),
+    }
+}
+
+/// Print metadata and sample positions from `config.toml`
+fn demo() -> Result<(), Box<dyn std::error::Error>> {
     // Load configuration
     let config_path = "config.toml";
 
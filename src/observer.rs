@@ -0,0 +1,344 @@
+//! Ground-based observers: geodetic site coordinates, topocentric positions
+//! and horizontal (altitude/azimuth) coordinates
+
+use std::f64::consts::{PI, TAU};
+
+use crate::apparent::{self, ASTRONOMICAL_UNIT};
+use crate::ephemeris::{Ephemeris, Position, State};
+use crate::frames::{self, Matrix3, ARCSEC};
+use crate::time::{self, JulianDate};
+use crate::{Error, Result};
+
+/// Mean angular velocity of the Earth (rad/day)
+const EARTH_ANGULAR_VELOCITY: f64 = 7.292115e-5 * 86400.0;
+
+/// Reference ellipsoid for geodetic coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ellipsoid {
+    /// World Geodetic System 1984
+    #[default]
+    Wgs84,
+    /// Geodetic Reference System 1980
+    Grs80,
+}
+
+impl Ellipsoid {
+    /// Equatorial radius (km)
+    pub fn equatorial_radius(&self) -> f64 {
+        6378.137
+    }
+
+    /// Flattening
+    pub fn flattening(&self) -> f64 {
+        match self {
+            Ellipsoid::Wgs84 => 1.0 / 298.257223563,
+            Ellipsoid::Grs80 => 1.0 / 298.257222101,
+        }
+    }
+}
+
+/// Coordinates of the celestial intermediate pole relative to the ITRS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolarMotion {
+    /// x coordinate (arcseconds)
+    pub xp: f64,
+    /// y coordinate (arcseconds)
+    pub yp: f64,
+}
+
+/// Atmospheric conditions used for the refraction correction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Refraction {
+    /// Pressure (hPa)
+    pub pressure: f64,
+    /// Temperature (degrees Celsius)
+    pub temperature: f64,
+}
+
+impl Default for Refraction {
+    fn default() -> Self {
+        Self {
+            pressure: 1010.0,
+            temperature: 10.0,
+        }
+    }
+}
+
+impl Refraction {
+    /// Refraction (degrees) to add to a true, airless altitude (degrees)
+    ///
+    /// Uses Saemundsson's formula; no correction is applied below -1 degree.
+    pub fn correction(&self, altitude: f64) -> f64 {
+        if !(-1.0..=90.0).contains(&altitude) {
+            return 0.0;
+        }
+        let arcmin = 1.02 / (altitude + 10.3 / (altitude + 5.11)).to_radians().tan();
+        let scale = (self.pressure / 1010.0) * (283.0 / (273.0 + self.temperature));
+        (arcmin / 60.0 * scale).max(0.0)
+    }
+
+    /// Refraction (degrees) already contained in an observed, apparent
+    /// altitude (degrees)
+    ///
+    /// Uses Bennett's formula; no correction is applied below -1 degree.
+    pub fn apparent_correction(&self, altitude: f64) -> f64 {
+        if !(-1.0..=90.0).contains(&altitude) {
+            return 0.0;
+        }
+        let arcmin = 1.0 / (altitude + 7.31 / (altitude + 4.4)).to_radians().tan();
+        let scale = (self.pressure / 1010.0) * (283.0 / (273.0 + self.temperature));
+        (arcmin / 60.0 * scale).max(0.0)
+    }
+}
+
+/// Horizontal coordinates of a body seen by an observer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HorizontalCoordinates {
+    /// Altitude above the horizon (degrees)
+    pub altitude: f64,
+    /// Azimuth measured from north through east (degrees, 0-360)
+    pub azimuth: f64,
+    /// Distance from the observer (AU)
+    pub distance: f64,
+}
+
+/// Observer on the surface of the Earth
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    /// Geodetic latitude (degrees, north positive)
+    pub latitude: f64,
+    /// Longitude (degrees, east positive)
+    pub longitude: f64,
+    /// Height above the ellipsoid (metres)
+    pub height: f64,
+    /// Reference ellipsoid
+    pub ellipsoid: Ellipsoid,
+    /// Optional polar motion
+    pub polar_motion: Option<PolarMotion>,
+}
+
+impl Observer {
+    /// Create an observer from geodetic coordinates on the WGS84 ellipsoid
+    ///
+    /// # Arguments
+    /// * `latitude` - Geodetic latitude in degrees (-90 to 90)
+    /// * `longitude` - Longitude in degrees, east positive (-180 to 360)
+    /// * `height` - Height above the ellipsoid in metres
+    ///
+    /// # Example
+    /// ```
+    /// use rust_jpl::observer::Observer;
+    /// let greenwich = Observer::new(51.4769, -0.0005, 46.0)?;
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn new(latitude: f64, longitude: f64, height: f64) -> Result<Self> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(Error::Config(format!("Invalid latitude: {}", latitude)));
+        }
+        if !(-180.0..=360.0).contains(&longitude) {
+            return Err(Error::Config(format!("Invalid longitude: {}", longitude)));
+        }
+        if !height.is_finite() {
+            return Err(Error::Config(format!("Invalid height: {}", height)));
+        }
+
+        Ok(Self {
+            latitude,
+            longitude,
+            height,
+            ellipsoid: Ellipsoid::default(),
+            polar_motion: None,
+        })
+    }
+
+    /// Use a different reference ellipsoid
+    pub fn with_ellipsoid(mut self, ellipsoid: Ellipsoid) -> Self {
+        self.ellipsoid = ellipsoid;
+        self
+    }
+
+    /// Apply polar motion (arcseconds) when rotating between frames
+    pub fn with_polar_motion(mut self, xp: f64, yp: f64) -> Self {
+        self.polar_motion = Some(PolarMotion { xp, yp });
+        self
+    }
+
+    /// Geocentric position of the site in the terrestrial frame (km)
+    pub fn terrestrial_position(&self) -> Position {
+        let a = self.ellipsoid.equatorial_radius();
+        let f = self.ellipsoid.flattening();
+        let e2 = f * (2.0 - f);
+        let (sin_lat, cos_lat) = self.latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.to_radians().sin_cos();
+        let n = a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        let h = self.height / 1000.0;
+
+        Position::new(
+            (n + h) * cos_lat * cos_lon,
+            (n + h) * cos_lat * sin_lon,
+            (n * (1.0 - e2) + h) * sin_lat,
+        )
+    }
+
+    /// Rotation from the GCRS to the terrestrial frame at `jd` (TT)
+    ///
+    /// Combines precession, nutation (from the ephemeris when it carries
+    /// nutation angles), Greenwich apparent sidereal time and, if set, polar
+    /// motion.
+    pub fn celestial_to_terrestrial(&self, eph: &Ephemeris, jd: JulianDate) -> Result<Matrix3> {
+        let jd_ut1 = time::tt_to_ut1(jd);
+        let eps = frames::mean_obliquity(jd.jd);
+        let (dpsi, deps) = frames::nutation_angles(eph, jd)?;
+        let np = frames::nutation_matrix(eps, dpsi, deps).mul(&frames::precession_matrix(jd.jd));
+        let gast =
+            time::greenwich_mean_sidereal_time(jd_ut1, jd) + time::equation_of_equinoxes(jd, dpsi);
+        let rotation = frames::rotation_z(gast).mul(&np);
+
+        Ok(match self.polar_motion {
+            Some(pm) => frames::rotation_x(-pm.yp * ARCSEC)
+                .mul(&frames::rotation_y(-pm.xp * ARCSEC))
+                .mul(&rotation),
+            None => rotation,
+        })
+    }
+
+    /// Local apparent sidereal time (radians, 0-2π) at `jd` (TT)
+    pub fn local_sidereal_time(&self, eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+        let gast = time::greenwich_apparent_sidereal_time_with(eph, time::tt_to_ut1(jd), jd)?;
+        Ok(time::local_sidereal_time(gast, self.longitude))
+    }
+
+    /// Geocentric state of the observer in the GCRS (AU, AU/day)
+    pub fn geocentric_state(&self, eph: &Ephemeris, jd: JulianDate) -> Result<State> {
+        let au = eph.get_constant("AU").unwrap_or(ASTRONOMICAL_UNIT);
+        let r = self.terrestrial_position();
+        let v = Position::new(
+            -EARTH_ANGULAR_VELOCITY * r.y,
+            EARTH_ANGULAR_VELOCITY * r.x,
+            0.0,
+        );
+        let to_celestial = self.celestial_to_terrestrial(eph, jd)?.transpose();
+        Ok(State::new(
+            to_celestial.apply(&r) * (1.0 / au),
+            to_celestial.apply(&v) * (1.0 / au),
+        ))
+    }
+
+    /// Barycentric state of the observer (AU, AU/day)
+    pub fn barycentric_state(&self, eph: &Ephemeris, jd: JulianDate) -> Result<State> {
+        Ok(eph.get_state("Earth", jd)? + self.geocentric_state(eph, jd)?)
+    }
+
+    /// Astrometric topocentric position of a body, corrected for light time
+    ///
+    /// # Arguments
+    /// * `eph` - Loaded ephemeris
+    /// * `body_name` - Target body (e.g., "Moon", "Sun", "Mars")
+    /// * `jd` - Julian date (TDB)
+    ///
+    /// # Returns
+    /// Position relative to the observer in AU, ICRF axes
+    pub fn topocentric(
+        &self,
+        eph: &Ephemeris,
+        body_name: &str,
+        jd: JulianDate,
+    ) -> Result<Position> {
+        let observer = self.barycentric_state(eph, jd)?;
+        apparent::light_time_corrected(eph, body_name, jd, &observer)
+    }
+
+    /// Apparent topocentric position of a body: light time and aberration
+    /// applied, GCRS axes (AU)
+    pub fn apparent(&self, eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<Position> {
+        let observer = self.barycentric_state(eph, jd)?;
+        let astrometric = apparent::light_time_corrected(eph, body_name, jd, &observer)?;
+        Ok(apparent::aberration(eph, &astrometric, &observer.velocity))
+    }
+
+    /// Altitude and azimuth of a body
+    ///
+    /// # Arguments
+    /// * `eph` - Loaded ephemeris
+    /// * `body_name` - Target body
+    /// * `jd` - Julian date (TDB)
+    /// * `refraction` - Atmospheric conditions, or `None` for airless altitudes
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::observer::{Observer, Refraction};
+    /// use rust_jpl::{Ephemeris, JulianDate};
+    /// let eph = Ephemeris::new("config.toml")?;
+    /// let site = Observer::new(19.8207, -155.4681, 4205.0)?;
+    /// let jd = JulianDate::from_calendar(2024, 1, 15, 6, 0, 0.0)?;
+    /// let mars = site.horizontal(&eph, "Mars", jd, Some(&Refraction::default()))?;
+    /// println!("Mars: alt {:.2}, az {:.2}", mars.altitude, mars.azimuth);
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn horizontal(
+        &self,
+        eph: &Ephemeris,
+        body_name: &str,
+        jd: JulianDate,
+        refraction: Option<&Refraction>,
+    ) -> Result<HorizontalCoordinates> {
+        let apparent = self.apparent(eph, body_name, jd)?;
+        let terrestrial = self.celestial_to_terrestrial(eph, jd)?.apply(&apparent);
+        let (altitude, azimuth) = self.local_horizon(&terrestrial);
+
+        Ok(HorizontalCoordinates {
+            altitude: altitude + refraction.map_or(0.0, |r| r.correction(altitude)),
+            azimuth,
+            distance: apparent.distance(),
+        })
+    }
+
+    /// Local hour angle of a body (radians, -π to π), positive west of the meridian
+    pub fn hour_angle(&self, eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<f64> {
+        let apparent = self.apparent(eph, body_name, jd)?;
+        let v = self.celestial_to_terrestrial(eph, jd)?.apply(&apparent);
+        let hour_angle = self.longitude.to_radians() - v.y.atan2(v.x);
+        Ok((hour_angle + PI).rem_euclid(TAU) - PI)
+    }
+
+    /// Altitude and azimuth (degrees) of a direction given in the terrestrial frame
+    fn local_horizon(&self, v: &Position) -> (f64, f64) {
+        let (sin_lat, cos_lat) = self.latitude.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = self.longitude.to_radians().sin_cos();
+        let east = -sin_lon * v.x + cos_lon * v.y;
+        let north = -sin_lat * cos_lon * v.x - sin_lat * sin_lon * v.y + cos_lat * v.z;
+        let up = cos_lat * cos_lon * v.x + cos_lat * sin_lon * v.y + sin_lat * v.z;
+
+        let altitude = up.atan2(east.hypot(north)).to_degrees();
+        let azimuth = east.atan2(north).to_degrees().rem_euclid(360.0);
+        (altitude, azimuth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terrestrial_position() {
+        let equator = Observer::new(0.0, 0.0, 0.0).unwrap().terrestrial_position();
+        assert!((equator.x - 6378.137).abs() < 1e-9);
+
+        let pole = Observer::new(90.0, 0.0, 0.0)
+            .unwrap()
+            .terrestrial_position();
+        assert!((pole.z - 6356.752314).abs() < 1e-6);
+
+        assert!(Observer::new(91.0, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_refraction_at_horizon() {
+        let r = Refraction::default().correction(0.0);
+        assert!((r * 60.0 - 28.9).abs() < 0.5);
+        assert_eq!(Refraction::default().correction(-5.0), 0.0);
+
+        let r = Refraction::default().apparent_correction(0.0);
+        assert!((r * 60.0 - 34.5).abs() < 0.5);
+    }
+}
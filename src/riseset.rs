@@ -0,0 +1,250 @@
+//! Rise, set and transit times of bodies seen from an observer
+
+use crate::apparent::ASTRONOMICAL_UNIT;
+use crate::ephemeris::Ephemeris;
+use crate::observer::{Observer, Refraction};
+use crate::search::{EventFinder, EventKind};
+use crate::time::JulianDate;
+use crate::Result;
+
+/// Sampling step of the search (days); events closer together may be missed
+const SEARCH_STEP: f64 = 1.0 / 24.0;
+
+/// Precision of the returned event times (days, about 10 ms)
+const TOLERANCE: f64 = 1e-7;
+
+/// Point of the disk that defines rise and set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Limb {
+    /// Upper limb touches the horizon (the usual convention for the Sun and Moon)
+    #[default]
+    Upper,
+    /// Centre of the disk is on the horizon
+    Centre,
+}
+
+/// Options for the rise, set and transit search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiseSetOptions {
+    /// Apparent altitude of the horizon in degrees (e.g. -6 for civil twilight)
+    pub horizon: f64,
+    /// Atmospheric refraction at the horizon, or `None` for airless horizons
+    pub refraction: Option<Refraction>,
+    /// Limb convention
+    pub limb: Limb,
+}
+
+impl Default for RiseSetOptions {
+    fn default() -> Self {
+        Self {
+            horizon: 0.0,
+            refraction: Some(Refraction::default()),
+            limb: Limb::Upper,
+        }
+    }
+}
+
+/// Kind of rise/set event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiseSetKind {
+    /// Body crosses the horizon upwards
+    Rise,
+    /// Body crosses the horizon downwards
+    Set,
+    /// Body crosses the local meridian above the pole
+    UpperTransit,
+    /// Body crosses the local meridian below the pole
+    LowerTransit,
+}
+
+/// Rise, set or transit event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiseSetEvent {
+    /// Kind of event
+    pub kind: RiseSetKind,
+    /// Time of the event (TDB)
+    pub jd: JulianDate,
+    /// Apparent altitude of the body centre (degrees)
+    pub altitude: f64,
+    /// Azimuth (degrees, from north through east)
+    pub azimuth: f64,
+}
+
+/// Find rise, set and transit times of a body between two dates
+///
+/// # Arguments
+/// * `eph` - Loaded ephemeris
+/// * `observer` - Observer location
+/// * `body_name` - Target body (e.g., "Sun", "Moon", "Mars")
+/// * `start` - Start of the search window (TDB)
+/// * `end` - End of the search window (TDB)
+/// * `options` - Horizon, refraction and limb conventions
+///
+/// # Returns
+/// Events sorted by time
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::riseset::{find_events, RiseSetOptions};
+/// use rust_jpl::{Ephemeris, JulianDate, Observer};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let site = Observer::new(48.8566, 2.3522, 35.0)?;
+/// let start = JulianDate::from_calendar(2024, 3, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::new(start.jd + 1.0);
+/// for event in find_events(&eph, &site, "Mars", start, end, &RiseSetOptions::default())? {
+///     println!("{:?} at JD {:.5}", event.kind, event.jd.as_f64());
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_events(
+    eph: &Ephemeris,
+    observer: &Observer,
+    body_name: &str,
+    start: JulianDate,
+    end: JulianDate,
+    options: &RiseSetOptions,
+) -> Result<Vec<RiseSetEvent>> {
+    let au = eph.get_constant("AU").unwrap_or(ASTRONOMICAL_UNIT);
+    let radius = match options.limb {
+        Limb::Upper => eph.get_radius(body_name).unwrap_or(0.0),
+        Limb::Centre => 0.0,
+    };
+    let refraction = options
+        .refraction
+        .map_or(0.0, |r| r.apparent_correction(options.horizon));
+
+    // Geometric altitude of the chosen limb relative to the refracted horizon
+    let altitude = |jd: JulianDate| -> Result<f64> {
+        let h = observer.horizontal(eph, body_name, jd, None)?;
+        let semi_diameter = (radius / (h.distance * au)).min(1.0).asin().to_degrees();
+        Ok(h.altitude + semi_diameter + refraction - options.horizon)
+    };
+    let meridian =
+        |jd: JulianDate| -> Result<f64> { Ok(observer.hour_angle(eph, body_name, jd)?.sin()) };
+
+    let finder = EventFinder::for_ephemeris(eph)
+        .with_step(SEARCH_STEP)
+        .with_tolerance(TOLERANCE);
+    let mut events = Vec::new();
+    for root in finder.roots(altitude, start, end)? {
+        let kind = if root.kind == EventKind::Rising {
+            RiseSetKind::Rise
+        } else {
+            RiseSetKind::Set
+        };
+        events.push(event(eph, observer, body_name, root.jd, kind, options)?);
+    }
+    for root in finder.roots(meridian, start, end)? {
+        let kind = if root.kind == EventKind::Rising {
+            RiseSetKind::UpperTransit
+        } else {
+            RiseSetKind::LowerTransit
+        };
+        events.push(event(eph, observer, body_name, root.jd, kind, options)?);
+    }
+
+    events.sort_by(|a, b| a.jd.jd.total_cmp(&b.jd.jd));
+    Ok(events)
+}
+
+fn event(
+    eph: &Ephemeris,
+    observer: &Observer,
+    body_name: &str,
+    jd: JulianDate,
+    kind: RiseSetKind,
+    options: &RiseSetOptions,
+) -> Result<RiseSetEvent> {
+    let h = observer.horizontal(eph, body_name, jd, options.refraction.as_ref())?;
+    Ok(RiseSetEvent {
+        kind,
+        jd,
+        altitude: h.altitude,
+        azimuth: h.azimuth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::model;
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn test_sun_events_at_solstice() {
+        let eph = model();
+        let site = Observer::new(48.8566, 2.3522, 35.0).unwrap();
+        let (start, end) = (JulianDate::new(2451716.5), JulianDate::new(2451718.5));
+        let options = RiseSetOptions {
+            horizon: 0.0,
+            refraction: None,
+            limb: Limb::Centre,
+        };
+        let events = find_events(&eph, &site, "Sun", start, end, &options).unwrap();
+
+        let kinds: Vec<RiseSetKind> = events.iter().map(|e| e.kind).collect();
+        use RiseSetKind::*;
+        assert_eq!(
+            kinds,
+            [
+                Rise,
+                UpperTransit,
+                Set,
+                LowerTransit,
+                Rise,
+                UpperTransit,
+                Set,
+                LowerTransit
+            ]
+        );
+        for event in &events {
+            match event.kind {
+                Rise | Set => assert!(event.altitude.abs() < 1e-3),
+                UpperTransit => {
+                    assert!(site.hour_angle(&eph, "Sun", event.jd).unwrap().abs() < 1e-6);
+                    assert!((event.azimuth - 180.0).abs() < 1e-3);
+                }
+                LowerTransit => assert!(event.altitude < 0.0),
+            }
+        }
+        assert!(events[0].azimuth < 90.0 && events[2].azimuth > 270.0);
+
+        // Semi-diurnal arc from the declination read off the transit altitude
+        let declination = (events[1].altitude + site.latitude - 90.0).to_radians();
+        let arc = (-site.latitude.to_radians().tan() * declination.tan()).acos();
+        let solar_day = events[5].jd.jd - events[1].jd.jd;
+        let day_length = events[2].jd.jd - events[0].jd.jd;
+        assert!((day_length - arc / TAU * 2.0 * solar_day).abs() * 86400.0 < 30.0);
+    }
+
+    #[test]
+    fn test_limb_and_refraction_advance_sunrise() {
+        let eph = model();
+        let site = Observer::new(48.8566, 2.3522, 35.0).unwrap();
+        let (start, end) = (JulianDate::new(2451716.5), JulianDate::new(2451717.5));
+        let centre = RiseSetOptions {
+            refraction: None,
+            limb: Limb::Centre,
+            ..RiseSetOptions::default()
+        };
+        let events = |options: &RiseSetOptions| {
+            find_events(&eph, &site, "Sun", start, end, options).unwrap()
+        };
+        let (geometric, apparent) = (events(&centre), events(&RiseSetOptions::default()));
+
+        // Hour angle at which the centre is at the given altitude
+        let declination = (geometric[1].altitude + site.latitude - 90.0).to_radians();
+        let (sin_lat, cos_lat) = site.latitude.to_radians().sin_cos();
+        let hour_angle = |altitude: f64| {
+            ((altitude.to_radians().sin() - sin_lat * declination.sin())
+                / (cos_lat * declination.cos()))
+            .acos()
+        };
+        let depression = (696000.0f64 / 149597870.7).asin().to_degrees()
+            + Refraction::default().apparent_correction(0.0);
+        let expected = (hour_angle(-depression) - hour_angle(0.0)) / TAU;
+        let advance = geometric[0].jd.jd - apparent[0].jd.jd;
+        assert_eq!(apparent[0].kind, RiseSetKind::Rise);
+        assert!((advance - expected).abs() * 86400.0 < 5.0);
+    }
+}
@@ -0,0 +1,457 @@
+//! Generic event search over scalar functions of time
+//!
+//! Modelled on the SPICE GF subsystem: the caller supplies a function of
+//! time, typically a distance or angle computed from [`Ephemeris`] queries,
+//! and the search finds its roots, threshold crossings, local extrema and
+//! the windows in which it satisfies a relation.
+//!
+//! The function is sampled on a fixed grid and each bracketed event is
+//! refined by bisection, so events closer together than the step may be
+//! missed. [`EventFinder::for_ephemeris`] aligns the grid with the
+//! ephemeris records so that no step straddles a record boundary.
+
+use crate::ephemeris::Ephemeris;
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Default precision of event times (days, about 0.01 s)
+const DEFAULT_TOLERANCE: f64 = 1e-7;
+
+/// Finest precision of event times (days, about 0.1 ms), above the
+/// resolution of a Julian date in the DE range
+const MIN_TOLERANCE: f64 = 1e-9;
+
+/// Most steps taken by bisection and golden-section search
+const MAX_ITERATIONS: usize = 200;
+
+/// Default number of samples per ephemeris record
+const SAMPLES_PER_RECORD: f64 = 8.0;
+
+/// Kind of event found by a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// The function crosses the threshold upwards
+    Rising,
+    /// The function crosses the threshold downwards
+    Falling,
+    /// Local minimum
+    Minimum,
+    /// Local maximum
+    Maximum,
+}
+
+/// Event found by a search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+    /// Time of the event
+    pub jd: JulianDate,
+    /// Function value at the event (the threshold for crossings)
+    pub value: f64,
+    /// Kind of event
+    pub kind: EventKind,
+}
+
+/// Condition on the function value for [`EventFinder::windows`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relation {
+    /// Function greater than the threshold
+    Above(f64),
+    /// Function less than the threshold
+    Below(f64),
+}
+
+/// Time interval in which a condition holds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    /// Start of the interval
+    pub start: JulianDate,
+    /// End of the interval
+    pub end: JulianDate,
+}
+
+impl Interval {
+    /// Length of the interval (days)
+    pub fn duration(&self) -> f64 {
+        self.end.jd - self.start.jd
+    }
+}
+
+/// Event search over functions of time
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::search::EventFinder;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2025, 1, 1, 0, 0, 0.0)?;
+/// // Closest approaches of Mars to the Earth
+/// let distance = |jd| Ok((eph.get_position("Mars", jd)? - eph.get_position("Earth", jd)?).distance());
+/// for event in EventFinder::for_ephemeris(&eph).extrema(distance, start, end)? {
+///     println!("{:?} at JD {:.5}: {:.4} AU", event.kind, event.jd.as_f64(), event.value);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventFinder {
+    step: f64,
+    tolerance: f64,
+    granule: Option<(f64, f64)>,
+    bounds: Option<(f64, f64)>,
+}
+
+impl EventFinder {
+    /// Create a search sampling every `step` days
+    pub fn new(step: f64) -> Self {
+        Self {
+            step,
+            tolerance: DEFAULT_TOLERANCE,
+            granule: None,
+            bounds: None,
+        }
+    }
+
+    /// Create a search whose grid follows the records of an ephemeris
+    ///
+    /// The default step divides each record into eight samples; a step set
+    /// with [`with_step`](Self::with_step) is shortened so that it divides
+    /// the record length evenly. Derivatives taken by
+    /// [`extrema`](Self::extrema) stay within the ephemeris range.
+    pub fn for_ephemeris(eph: &Ephemeris) -> Self {
+        let metadata = eph.get_metadata();
+        Self {
+            step: metadata.interval_days / SAMPLES_PER_RECORD,
+            tolerance: DEFAULT_TOLERANCE,
+            granule: Some((metadata.julian_start, metadata.interval_days)),
+            bounds: Some(eph.get_date_range()),
+        }
+    }
+
+    /// Set the sampling step (days)
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Set the precision of event times (days)
+    ///
+    /// Values below 1e-9 days, including zero and NaN, are raised to it.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.max(MIN_TOLERANCE);
+        self
+    }
+
+    /// Find the roots of `f` between two dates
+    pub fn roots<F>(&self, f: F, start: JulianDate, end: JulianDate) -> Result<Vec<Event>>
+    where
+        F: FnMut(JulianDate) -> Result<f64>,
+    {
+        self.crossings(f, 0.0, start, end)
+    }
+
+    /// Find the times at which `f` crosses `threshold`
+    pub fn crossings<F>(
+        &self,
+        mut f: F,
+        threshold: f64,
+        start: JulianDate,
+        end: JulianDate,
+    ) -> Result<Vec<Event>>
+    where
+        F: FnMut(JulianDate) -> Result<f64>,
+    {
+        let mut g = |jd: f64| -> Result<f64> { Ok(f(JulianDate::new(jd))? - threshold) };
+        let grid = self.grid(start.jd, end.jd)?;
+
+        let mut events = Vec::new();
+        let mut g0 = g(grid[0])?;
+        for pair in grid.windows(2) {
+            let g1 = g(pair[1])?;
+            if (g0 < 0.0) != (g1 < 0.0) {
+                events.push(Event {
+                    jd: JulianDate::new(bisect(&mut g, pair[0], pair[1], g0, self.tolerance)?),
+                    value: threshold,
+                    kind: if g0 < 0.0 {
+                        EventKind::Rising
+                    } else {
+                        EventKind::Falling
+                    },
+                });
+            }
+            g0 = g1;
+        }
+
+        Ok(events)
+    }
+
+    /// Find the local minima and maxima of `f`
+    ///
+    /// Extrema are the roots of the central-difference derivative of `f`.
+    /// With [`for_ephemeris`](Self::for_ephemeris) the difference becomes
+    /// one-sided near the ends of the ephemeris, so that searches may run up
+    /// to its bounds.
+    pub fn extrema<F>(&self, mut f: F, start: JulianDate, end: JulianDate) -> Result<Vec<Event>>
+    where
+        F: FnMut(JulianDate) -> Result<f64>,
+    {
+        let h = self.step * 1e-3;
+        let (first, last) = self.bounds.unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+        let derivative = |jd: JulianDate| -> Result<f64> {
+            let (behind, ahead) = ((jd.jd - h).max(first), (jd.jd + h).min(last));
+            let change = f(JulianDate::new(ahead))? - f(JulianDate::new(behind))?;
+            Ok(change / (ahead - behind))
+        };
+        let roots = self.roots(derivative, start, end)?;
+
+        roots
+            .into_iter()
+            .map(|root| {
+                Ok(Event {
+                    jd: root.jd,
+                    value: f(root.jd)?,
+                    kind: match root.kind {
+                        EventKind::Rising => EventKind::Minimum,
+                        _ => EventKind::Maximum,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Find the intervals in which `f` satisfies a relation
+    pub fn windows<F>(
+        &self,
+        mut f: F,
+        relation: Relation,
+        start: JulianDate,
+        end: JulianDate,
+    ) -> Result<Vec<Interval>>
+    where
+        F: FnMut(JulianDate) -> Result<f64>,
+    {
+        let (threshold, entering) = match relation {
+            Relation::Above(threshold) => (threshold, EventKind::Rising),
+            Relation::Below(threshold) => (threshold, EventKind::Falling),
+        };
+        let initial = f(start)?;
+        let mut open = match relation {
+            Relation::Above(_) if initial > threshold => Some(start),
+            Relation::Below(_) if initial < threshold => Some(start),
+            _ => None,
+        };
+
+        let mut intervals = Vec::new();
+        for event in self.crossings(&mut f, threshold, start, end)? {
+            if event.kind == entering {
+                open = Some(event.jd);
+            } else if let Some(opened) = open.take() {
+                intervals.push(Interval {
+                    start: opened,
+                    end: event.jd,
+                });
+            }
+        }
+        if let Some(opened) = open {
+            intervals.push(Interval { start: opened, end });
+        }
+
+        Ok(intervals)
+    }
+
+    /// Sample times covering `[start, end]`
+    fn grid(&self, start: f64, end: f64) -> Result<Vec<f64>> {
+        if self.step.is_nan() || self.step <= 0.0 {
+            return Err(Error::Config("Search step must be positive".to_string()));
+        }
+        if end < start {
+            return Err(Error::Config(
+                "Search window ends before it starts".to_string(),
+            ));
+        }
+
+        // Without an ephemeris the grid starts at the window start; with one
+        // it starts at the first record and the step divides each record
+        let (origin, step) = match self.granule {
+            Some((origin, length)) => (origin, length / (length / self.step).ceil()),
+            None => (start, self.step),
+        };
+
+        let mut times = vec![start];
+        let mut k = ((start - origin) / step).floor() + 1.0;
+        loop {
+            let t = origin + k * step;
+            if t >= end {
+                break;
+            }
+            if t > start {
+                times.push(t);
+            }
+            k += 1.0;
+        }
+        if end > start {
+            times.push(end);
+        }
+        Ok(times)
+    }
+}
+
+/// Refine a sign change of `f` on `[a, b]` by bisection, given `fa = f(a)`
+///
+/// Stops after [`MAX_ITERATIONS`] steps if `tolerance` cannot be reached.
+pub(crate) fn bisect<F>(f: &mut F, a: f64, b: f64, fa: f64, tolerance: f64) -> Result<f64>
+where
+    F: FnMut(f64) -> Result<f64>,
+{
+    let (mut a, mut b, mut fa) = (a, b, fa);
+    for _ in 0..MAX_ITERATIONS {
+        if b - a <= tolerance {
+            break;
+        }
+        let mid = 0.5 * (a + b);
+        let fm = f(mid)?;
+        if (fm < 0.0) == (fa < 0.0) {
+            a = mid;
+            fa = fm;
+        } else {
+            b = mid;
+        }
+    }
+    Ok(0.5 * (a + b))
+}
+
+/// Locate the minimum of a unimodal function on `[a, b]` by golden-section
+/// search, returning the abscissa and value of the minimum
+///
+/// Stops after [`MAX_ITERATIONS`] steps if `tolerance` cannot be reached.
+pub(crate) fn minimize<F>(mut f: F, a: f64, b: f64, tolerance: f64) -> Result<(f64, f64)>
+where
+    F: FnMut(f64) -> Result<f64>,
+{
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = (a, b);
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let (mut fc, mut fd) = (f(c)?, f(d)?);
+
+    for _ in 0..MAX_ITERATIONS {
+        if b - a <= tolerance {
+            break;
+        }
+        if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - ratio * (b - a);
+            fc = f(c)?;
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + ratio * (b - a);
+            fd = f(d)?;
+        }
+    }
+
+    let x = 0.5 * (a + b);
+    Ok((x, f(x)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::{model, MODEL_MOON};
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn sine(jd: JulianDate) -> Result<f64> {
+        Ok(jd.jd.sin())
+    }
+
+    #[test]
+    fn test_roots() {
+        let finder = EventFinder::new(0.25).with_tolerance(1e-10);
+        let roots = finder
+            .roots(sine, JulianDate::new(0.5), JulianDate::new(10.0))
+            .unwrap();
+        assert_eq!(roots.len(), 3);
+        assert!((roots[0].jd.jd - PI).abs() < 1e-9);
+        assert_eq!(roots[0].kind, EventKind::Falling);
+        assert_eq!(roots[1].kind, EventKind::Rising);
+    }
+
+    #[test]
+    fn test_extrema_and_windows() {
+        let finder = EventFinder::new(0.5).with_tolerance(1e-10);
+        let (start, end) = (JulianDate::new(0.0), JulianDate::new(7.0));
+
+        let extrema = finder.extrema(sine, start, end).unwrap();
+        assert_eq!(extrema.len(), 2);
+        assert!((extrema[0].jd.jd - FRAC_PI_2).abs() < 1e-8);
+        assert_eq!(extrema[0].kind, EventKind::Maximum);
+        assert!((extrema[1].value + 1.0).abs() < 1e-12);
+
+        let windows = finder
+            .windows(sine, Relation::Above(0.5), start, end)
+            .unwrap();
+        assert_eq!(windows.len(), 2);
+        assert!((windows[0].duration() - 2.0 * PI / 3.0).abs() < 1e-9);
+        assert_eq!(windows[1].end, end);
+    }
+
+    #[test]
+    fn test_tolerance_floor() {
+        // Below the resolution of a Julian date bisection could never close
+        let shifted = |jd: JulianDate| Ok((jd.jd - 2451545.0).sin());
+        for tolerance in [0.0, -1.0, 1e-15, f64::NAN] {
+            let roots = EventFinder::new(0.25)
+                .with_tolerance(tolerance)
+                .roots(
+                    shifted,
+                    JulianDate::new(2451545.5),
+                    JulianDate::new(2451555.0),
+                )
+                .unwrap();
+            assert_eq!(roots.len(), 3);
+            assert!((roots[0].jd.jd - 2451545.0 - PI).abs() < 1e-8);
+        }
+
+        let (x, _) = minimize(|t| Ok((t - 2451545.25).powi(2)), 2451545.0, 2451546.0, 0.0).unwrap();
+        assert!((x - 2451545.25).abs() < 1e-6);
+        let mut line = |t: f64| Ok(t - 2451545.25);
+        let root = bisect(&mut line, 2451545.0, 2451546.0, -0.25, f64::NAN).unwrap();
+        assert!((root - 2451545.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_extrema_at_ephemeris_bounds() {
+        let eph = model();
+        let (first, last) = eph.get_date_range();
+        let distance = |jd: JulianDate| {
+            Ok((eph.get_position("Moon", jd)? - eph.get_position("Earth", jd)?).distance())
+        };
+
+        // The derivative may not sample outside [first, last]
+        let extrema = EventFinder::for_ephemeris(&eph)
+            .with_tolerance(1e-6)
+            .extrema(distance, JulianDate::new(first), JulianDate::new(last))
+            .unwrap();
+        assert_eq!(extrema.len(), 58);
+        for event in &extrema {
+            let mean = match event.kind {
+                EventKind::Minimum => 0.0,
+                _ => 180.0,
+            };
+            let expected = MODEL_MOON.passage(event.jd.jd - 1.0, mean);
+            assert!((event.jd.jd - expected).abs() < 1e-4);
+        }
+        assert!(EventFinder::new(1.0)
+            .extrema(distance, JulianDate::new(first), JulianDate::new(last))
+            .is_err());
+    }
+
+    #[test]
+    fn test_minimize() {
+        let (x, fx) = minimize(|t| Ok((t - 1.25).powi(2) + 3.0), -4.0, 4.0, 1e-9).unwrap();
+        assert!((x - 1.25).abs() < 1e-6);
+        assert!((fx - 3.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,198 @@
+//! Equinoxes, solstices and solar terms from the apparent solar longitude
+//!
+//! Longitudes are apparent geocentric ecliptic longitudes of the Sun
+//! referred to the true equinox of date. Precession uses the IAU 2006
+//! polynomials, so accuracy degrades for epochs many millennia from J2000
+//! even though the search itself runs over the whole ephemeris range.
+
+use crate::apparent;
+use crate::ephemeris::Ephemeris;
+use crate::search::{EventFinder, EventKind};
+use crate::time::{self, JulianDate};
+use crate::Result;
+
+/// Sampling step of the search (days)
+const LONGITUDE_STEP: f64 = 2.0;
+
+/// Precision of the returned times (days, about 0.01 s)
+const LONGITUDE_TOLERANCE: f64 = 1e-7;
+
+/// Equinox or solstice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Season {
+    /// Solar longitude 0 degrees
+    MarchEquinox,
+    /// Solar longitude 90 degrees
+    JuneSolstice,
+    /// Solar longitude 180 degrees
+    SeptemberEquinox,
+    /// Solar longitude 270 degrees
+    DecemberSolstice,
+}
+
+/// Instant at which the Sun reaches a given apparent longitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarLongitudeEvent {
+    /// Apparent solar longitude reached (degrees)
+    pub longitude: f64,
+    /// Time of the event (TDB)
+    pub tdb: JulianDate,
+    /// Time of the event (UTC)
+    pub utc: JulianDate,
+}
+
+/// Equinox or solstice event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeasonEvent {
+    /// Season that begins
+    pub season: Season,
+    /// Time of the event (TDB)
+    pub tdb: JulianDate,
+    /// Time of the event (UTC)
+    pub utc: JulianDate,
+}
+
+/// Apparent geocentric ecliptic longitude of the Sun (degrees, 0-360)
+pub fn solar_longitude(eph: &Ephemeris, jd: JulianDate) -> Result<f64> {
+    Ok(apparent::ecliptic_of_date(eph, "Sun", jd)?.longitude)
+}
+
+/// Find the equinoxes and solstices between two dates
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::seasons::find_seasons;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2025, 1, 1, 0, 0, 0.0)?;
+/// for event in find_seasons(&eph, start, end)? {
+///     let utc = event.utc.to_calendar();
+///     println!("{:?}: {}-{:02}-{:02} {:02}:{:02}", event.season, utc.year, utc.month, utc.day, utc.hour, utc.minute);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_seasons(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<SeasonEvent>> {
+    Ok(find_longitude_multiples(eph, 90.0, 0.0, start, end)?
+        .into_iter()
+        .map(|event| SeasonEvent {
+            season: match (event.longitude / 90.0).round() as i64 % 4 {
+                0 => Season::MarchEquinox,
+                1 => Season::JuneSolstice,
+                2 => Season::SeptemberEquinox,
+                _ => Season::DecemberSolstice,
+            },
+            tdb: event.tdb,
+            utc: event.utc,
+        })
+        .collect())
+}
+
+/// Find the 24 solar terms (multiples of 15 degrees of solar longitude)
+/// between two dates
+pub fn find_solar_terms(
+    eph: &Ephemeris,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<SolarLongitudeEvent>> {
+    find_longitude_multiples(eph, 15.0, 0.0, start, end)
+}
+
+/// Find the times at which the Sun reaches an apparent longitude
+///
+/// # Arguments
+/// * `eph` - Loaded ephemeris
+/// * `longitude` - Target apparent solar longitude (degrees)
+/// * `start` - Start of the search window (TDB)
+/// * `end` - End of the search window (TDB)
+pub fn find_solar_longitude(
+    eph: &Ephemeris,
+    longitude: f64,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<SolarLongitudeEvent>> {
+    find_longitude_multiples(eph, 360.0, longitude, start, end)
+}
+
+/// Find the times at which the solar longitude reaches `offset` plus a
+/// multiple of `spacing` degrees
+fn find_longitude_multiples(
+    eph: &Ephemeris,
+    spacing: f64,
+    offset: f64,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<SolarLongitudeEvent>> {
+    // Distance from the nearest target longitude, wrapping halfway between
+    // targets; only the upward crossings are events.
+    let distance = |jd: JulianDate| -> Result<f64> {
+        let longitude = solar_longitude(eph, jd)?;
+        Ok((longitude - offset + spacing / 2.0).rem_euclid(spacing) - spacing / 2.0)
+    };
+
+    let finder = EventFinder::for_ephemeris(eph)
+        .with_step(LONGITUDE_STEP)
+        .with_tolerance(LONGITUDE_TOLERANCE);
+    let mut events = Vec::new();
+    for root in finder.roots(distance, start, end)? {
+        if root.kind != EventKind::Rising {
+            continue;
+        }
+        let tdb = root.jd;
+        let reached = solar_longitude(eph, tdb)?;
+        let longitude =
+            (offset + ((reached - offset) / spacing).round() * spacing).rem_euclid(360.0);
+        events.push(SolarLongitudeEvent {
+            longitude,
+            tdb,
+            utc: time::tdb_to_utc(tdb),
+        });
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::model;
+
+    #[test]
+    fn test_find_seasons() {
+        let eph = model();
+        let (start, end) = (JulianDate::new(2451545.0), JulianDate::new(2451910.0));
+        let seasons = find_seasons(&eph, start, end).unwrap();
+        let kinds: Vec<Season> = seasons.iter().map(|e| e.season).collect();
+        assert_eq!(
+            kinds,
+            [
+                Season::MarchEquinox,
+                Season::JuneSolstice,
+                Season::SeptemberEquinox,
+                Season::DecemberSolstice
+            ]
+        );
+
+        // The solar longitude passes 0, 90, 180 and 270 degrees within a
+        // second of the returned instants
+        for (k, event) in seasons.iter().enumerate() {
+            let offset = |dt: f64| {
+                let jd = JulianDate::new(event.tdb.jd + dt / 86400.0);
+                (solar_longitude(&eph, jd).unwrap() - 90.0 * k as f64 + 180.0).rem_euclid(360.0)
+                    - 180.0
+            };
+            assert!(offset(0.0).abs() < 1e-5);
+            assert!(offset(-1.0) < 0.0 && offset(1.0) > 0.0);
+            assert!(((event.tdb.jd - event.utc.jd) * 86400.0 - 64.0).abs() < 1.0);
+        }
+
+        assert_eq!(find_solar_terms(&eph, start, end).unwrap().len(), 24);
+        let equinox = find_solar_longitude(&eph, 0.0, start, end).unwrap();
+        assert_eq!(equinox.len(), 1);
+        assert!((equinox[0].tdb.jd - seasons[0].tdb.jd).abs() < 1e-6);
+    }
+}
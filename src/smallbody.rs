@@ -0,0 +1,197 @@
+//! Comets and asteroids from Minor Planet Center orbital elements
+//!
+//! Small bodies are propagated as heliocentric two-body orbits and combined
+//! with the ephemeris Sun and Earth to give barycentric, geocentric and
+//! astrometric positions. MPC epochs are TT and are used as TDB, which
+//! differ by less than 2 ms.
+
+use crate::apparent;
+use crate::elements::OrbitalElements;
+use crate::ephemeris::{Ephemeris, Position, State};
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Heliocentric gravitational parameter implied by the Gaussian constant
+/// k = 0.01720209895 (AU^3/day^2), used by the MPC for osculating elements
+pub const GAUSSIAN_GM: f64 = 0.01720209895 * 0.01720209895;
+
+/// Comet or asteroid on a two-body heliocentric orbit
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmallBody {
+    /// Name or designation
+    pub name: String,
+    /// Heliocentric osculating elements
+    pub elements: OrbitalElements,
+}
+
+impl SmallBody {
+    /// Create a small body from heliocentric elements
+    pub fn new(name: &str, elements: OrbitalElements) -> Self {
+        Self {
+            name: name.to_string(),
+            elements,
+        }
+    }
+
+    /// Parse a line of the MPC asteroid orbit file (MPCORB.DAT format)
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::smallbody::SmallBody;
+    /// use rust_jpl::{Ephemeris, JulianDate};
+    /// let eph = Ephemeris::new("config.toml")?;
+    /// let line = std::fs::read_to_string("MPCORB.DAT")?.lines().nth(43).unwrap().to_string();
+    /// let ceres = SmallBody::from_mpcorb(&line)?;
+    /// let jd = JulianDate::from_calendar(2024, 6, 1, 0, 0, 0.0)?;
+    /// let position = ceres.astrometric(&eph, jd)?;
+    /// println!("{}: {:.4} AU from the Earth", ceres.name, position.distance());
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn from_mpcorb(line: &str) -> Result<Self> {
+        let epoch = unpack_epoch(field(line, 21, 25)?)?;
+        let eccentricity = number(line, 71, 79)?;
+        let semi_major_axis = number(line, 93, 103)?;
+        let name = field(line, 167, 194)
+            .or_else(|_| field(line, 1, 7))?
+            .to_string();
+
+        Ok(Self {
+            name,
+            elements: OrbitalElements {
+                epoch,
+                gm: GAUSSIAN_GM,
+                periapsis_distance: semi_major_axis * (1.0 - eccentricity),
+                eccentricity,
+                inclination: number(line, 60, 68)?,
+                ascending_node: number(line, 49, 57)?,
+                argument_of_periapsis: number(line, 38, 46)?,
+                mean_anomaly: number(line, 27, 35)?,
+            },
+        })
+    }
+
+    /// Parse a line of the MPC comet orbit file (CometEls.txt format)
+    pub fn from_comet_elements(line: &str) -> Result<Self> {
+        let year = number(line, 15, 18)? as i32;
+        let month = number(line, 20, 21)? as i32;
+        let day = number(line, 23, 29)?;
+        let midnight = JulianDate::from_calendar(year, month, day.trunc() as i32, 0, 0, 0.0)?;
+        let perihelion = JulianDate::new(midnight.jd + day.fract());
+        let name = field(line, 103, 158)
+            .or_else(|_| field(line, 1, 12))?
+            .to_string();
+
+        Ok(Self {
+            name,
+            elements: OrbitalElements {
+                epoch: perihelion,
+                gm: GAUSSIAN_GM,
+                periapsis_distance: number(line, 31, 39)?,
+                eccentricity: number(line, 42, 49)?,
+                inclination: number(line, 72, 79)?,
+                ascending_node: number(line, 62, 69)?,
+                argument_of_periapsis: number(line, 52, 59)?,
+                mean_anomaly: 0.0,
+            },
+        })
+    }
+
+    /// Heliocentric state from the two-body orbit (ICRF axes, AU and AU/day)
+    pub fn heliocentric_state(&self, jd: JulianDate) -> Result<State> {
+        self.elements.state_at(jd)
+    }
+
+    /// Barycentric state, adding the ephemeris Sun to the two-body orbit
+    pub fn barycentric_state(&self, eph: &Ephemeris, jd: JulianDate) -> Result<State> {
+        Ok(self.heliocentric_state(jd)? + eph.get_state("Sun", jd)?)
+    }
+
+    /// Geometric geocentric state
+    pub fn geocentric_state(&self, eph: &Ephemeris, jd: JulianDate) -> Result<State> {
+        Ok(self.barycentric_state(eph, jd)? - eph.get_state("Earth", jd)?)
+    }
+
+    /// Astrometric geocentric position corrected for light time (AU, ICRF
+    /// axes)
+    pub fn astrometric(&self, eph: &Ephemeris, jd: JulianDate) -> Result<Position> {
+        let c = apparent::speed_of_light(eph);
+        let earth = eph.get_state("Earth", jd)?.position;
+        let mut relative = self.barycentric_state(eph, jd)?.position - earth;
+        for _ in 0..3 {
+            let emitted = JulianDate::new(jd.jd - relative.distance() / c);
+            relative = self.barycentric_state(eph, emitted)?.position - earth;
+        }
+        Ok(relative)
+    }
+}
+
+/// Trimmed contents of the 1-based inclusive column range of a line
+fn field(line: &str, first: usize, last: usize) -> Result<&str> {
+    let value = line
+        .get(first - 1..last.min(line.len()))
+        .map(str::trim)
+        .unwrap_or("");
+    if value.is_empty() {
+        return Err(Error::Config(format!(
+            "Missing MPC orbit field in columns {}-{}",
+            first, last
+        )));
+    }
+    Ok(value)
+}
+
+fn number(line: &str, first: usize, last: usize) -> Result<f64> {
+    let value = field(line, first, last)?;
+    value.parse().map_err(|_| {
+        Error::Config(format!(
+            "Invalid number '{}' in MPC orbit columns {}-{}",
+            value, first, last
+        ))
+    })
+}
+
+/// Decode an MPC packed date such as `K2455` (2024 May 5, 0h TT)
+fn unpack_epoch(packed: &str) -> Result<JulianDate> {
+    let invalid = || Error::InvalidDate(format!("Invalid MPC packed epoch '{}'", packed));
+    let digit = |c: char| match c {
+        '1'..='9' => Some(c as i32 - '0' as i32),
+        'A'..='V' => Some(c as i32 - 'A' as i32 + 10),
+        _ => None,
+    };
+
+    let chars: Vec<char> = packed.chars().collect();
+    if chars.len() != 5 {
+        return Err(invalid());
+    }
+    let century = match chars[0] {
+        'I' => 1800,
+        'J' => 1900,
+        'K' => 2000,
+        _ => return Err(invalid()),
+    };
+    let year = packed[1..3].parse::<i32>().map_err(|_| invalid())?;
+    let month = digit(chars[3]).ok_or_else(invalid)?;
+    let day = digit(chars[4]).ok_or_else(invalid)?;
+    JulianDate::from_calendar(century + year, month, day, 0, 0, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mpcorb_line() {
+        let elements = "00001    3.33  0.15 K2455 188.70269   73.27343   80.25221   10.58780  0.0794013  0.21424651   2.7660512";
+        let line = format!("{:<166}{}", elements, "(1) Ceres");
+        let ceres = SmallBody::from_mpcorb(&line).unwrap();
+        assert_eq!(ceres.name, "(1) Ceres");
+        assert_eq!(
+            ceres.elements.epoch,
+            JulianDate::from_calendar(2024, 5, 5, 0, 0, 0.0).unwrap()
+        );
+        assert_eq!(ceres.elements.mean_anomaly, 188.70269);
+        assert_eq!(ceres.elements.inclination, 10.5878);
+        assert!((ceres.elements.semi_major_axis() - 2.7660512).abs() < 1e-9);
+        assert!((ceres.elements.mean_motion() - 0.21424651).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,448 @@
+//! SPICE SPK kernels
+//!
+//! Reads binary SPK files in the DAF format, such as the `sb441-n16`
+//! kernel of the sixteen asteroids perturbing DE441 or the SPKs generated
+//! by Horizons. Chebyshev segments of types 2 (position) and 3 (position
+//! and velocity) in the J2000 frame can be evaluated; other segments are
+//! listed but report an error when queried.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Mutex;
+
+use crate::ephemeris::{chebyshev, Position, State};
+use crate::time::JulianDate;
+use crate::{Error, Result};
+
+/// Size of a DAF record (bytes)
+const RECORD_BYTES: usize = 1024;
+
+/// Seconds per day
+const DAY_SECONDS: f64 = 86400.0;
+
+/// Julian date of the J2000 epoch, the origin of SPK ephemeris time
+const J2000: f64 = 2451545.0;
+
+/// NAIF frame code of J2000
+const J2000_FRAME: i32 = 1;
+
+/// NAIF IDs of the sixteen asteroids in the `sb441-n16` kernel
+pub const SB441_N16: [(&str, i32); 16] = [
+    ("Ceres", 2000001),
+    ("Pallas", 2000002),
+    ("Juno", 2000003),
+    ("Vesta", 2000004),
+    ("Iris", 2000007),
+    ("Hygiea", 2000010),
+    ("Eunomia", 2000015),
+    ("Psyche", 2000016),
+    ("Euphrosyne", 2000031),
+    ("Europa", 2000052),
+    ("Cybele", 2000065),
+    ("Sylvia", 2000087),
+    ("Thisbe", 2000088),
+    ("Camilla", 2000107),
+    ("Davida", 2000511),
+    ("Interamnia", 2000704),
+];
+
+/// Segment of an SPK kernel
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpkSegment {
+    /// Segment name from the DAF name record
+    pub name: String,
+    /// NAIF ID of the target body
+    pub target: i32,
+    /// NAIF ID of the center the target is referred to
+    pub center: i32,
+    /// NAIF frame code
+    pub frame: i32,
+    /// SPK data type
+    pub data_type: i32,
+    /// Start of coverage (TDB)
+    pub start: JulianDate,
+    /// End of coverage (TDB)
+    pub end: JulianDate,
+    /// Addresses of the first and last double of the segment data (1-based)
+    addresses: (usize, usize),
+    /// Chebyshev directory: start (s past J2000), interval (s), record size
+    /// and record count
+    directory: Option<(f64, f64, usize, usize)>,
+}
+
+impl SpkSegment {
+    /// Whether the segment covers a Julian date
+    pub fn covers(&self, jd: JulianDate) -> bool {
+        self.start.jd <= jd.jd && jd.jd <= self.end.jd
+    }
+}
+
+/// Binary SPK kernel
+pub struct SpkKernel {
+    path: String,
+    segments: Vec<SpkSegment>,
+    big_endian: bool,
+    file: Mutex<File>,
+}
+
+impl std::fmt::Debug for SpkKernel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpkKernel")
+            .field("path", &self.path)
+            .field("segments", &self.segments)
+            .finish()
+    }
+}
+
+impl SpkKernel {
+    /// Open an SPK kernel and read its segment summaries
+    ///
+    /// # Example
+    /// ```ignore
+    /// use rust_jpl::spk::SpkKernel;
+    /// let kernel = SpkKernel::open("assets/sb441-n16.bsp")?;
+    /// for segment in kernel.segments() {
+    ///     println!("{} relative to {}", segment.target, segment.center);
+    /// }
+    /// # Ok::<(), rust_jpl::Error>(())
+    /// ```
+    pub fn open(path: &str) -> Result<Self> {
+        Self::read(path).map_err(|err| err.in_file(path))
+    }
+
+    fn read(path: &str) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut record = [0u8; RECORD_BYTES];
+        file.read_exact(&mut record)?;
+
+        if &record[0..7] != b"DAF/SPK" {
+            return Err(Error::Ephemeris("Not a DAF/SPK file".to_string()));
+        }
+        let big_endian = match &record[88..96] {
+            b"BIG-IEEE" => true,
+            b"LTL-IEEE" => false,
+            // Pre-N0050 files carry no format string; ND is always 2
+            _ => i32::from_le_bytes([record[8], record[9], record[10], record[11]]) != 2,
+        };
+        let mut kernel = Self {
+            path: path.to_string(),
+            segments: Vec::new(),
+            big_endian,
+            file: Mutex::new(file),
+        };
+        let (nd, ni) = (kernel.int(&record[8..12]), kernel.int(&record[12..16]));
+        if nd != 2 || ni != 6 {
+            return Err(Error::Ephemeris(format!(
+                "ND={} NI={} but an SPK has ND=2 NI=6",
+                nd, ni
+            )));
+        }
+        let forward = kernel.int(&record[76..80]);
+        kernel.read_summaries(forward)?;
+        Ok(kernel)
+    }
+
+    /// Walk the linked list of summary records starting at `forward`
+    fn read_summaries(&mut self, forward: i32) -> Result<()> {
+        // A summary is two doubles and six integers packed into three doubles
+        const SUMMARY_DOUBLES: usize = 5;
+
+        let mut next = forward;
+        let mut visited = 0;
+        while next > 0 {
+            visited += 1;
+            if visited > 100_000 {
+                return Err(Error::Ephemeris("Summary records form a cycle".to_string()));
+            }
+
+            let address = (next as usize - 1) * RECORD_BYTES / 8 + 1;
+            let summary = self.read_doubles(address, RECORD_BYTES / 8)?;
+            let names = self.read_bytes((next as usize) * RECORD_BYTES, RECORD_BYTES)?;
+            next = summary[0] as i32;
+            let count = (summary[2] as usize).min((RECORD_BYTES / 8 - 3) / SUMMARY_DOUBLES);
+
+            for k in 0..count {
+                let words = &summary[3 + k * SUMMARY_DOUBLES..3 + (k + 1) * SUMMARY_DOUBLES];
+                let ints: Vec<i32> = words[2..]
+                    .iter()
+                    .flat_map(|w| {
+                        let bytes = self.to_bytes(*w);
+                        [self.int(&bytes[0..4]), self.int(&bytes[4..8])]
+                    })
+                    .collect();
+                let name_bytes = &names[k * SUMMARY_DOUBLES * 8..(k + 1) * SUMMARY_DOUBLES * 8];
+
+                let mut segment = SpkSegment {
+                    name: String::from_utf8_lossy(name_bytes)
+                        .trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+                        .to_string(),
+                    target: ints[0],
+                    center: ints[1],
+                    frame: ints[2],
+                    data_type: ints[3],
+                    start: JulianDate::new(J2000 + words[0] / DAY_SECONDS),
+                    end: JulianDate::new(J2000 + words[1] / DAY_SECONDS),
+                    addresses: (0, 0),
+                    directory: None,
+                };
+                if ints[4] < 1 || ints[5] < ints[4] {
+                    return Err(Error::Ephemeris(format!(
+                        "Segment '{}' has invalid data addresses {}..{}",
+                        segment.name, ints[4], ints[5]
+                    )));
+                }
+                segment.addresses = (ints[4] as usize, ints[5] as usize);
+                if matches!(segment.data_type, 2 | 3) && segment.addresses.1 >= 4 {
+                    let d = self.read_doubles(segment.addresses.1 - 3, 4)?;
+                    segment.directory = Some((d[0], d[1], d[2] as usize, d[3] as usize));
+                }
+                self.segments.push(segment);
+            }
+        }
+        Ok(())
+    }
+
+    /// Path the kernel was loaded from
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Segments of the kernel in file order
+    pub fn segments(&self) -> &[SpkSegment] {
+        &self.segments
+    }
+
+    /// Whether the kernel has data for a body at a Julian date
+    pub fn covers(&self, target: i32, jd: JulianDate) -> bool {
+        self.segment(target, jd).is_some()
+    }
+
+    /// Center of the segment covering a body at a Julian date
+    pub fn center(&self, target: i32, jd: JulianDate) -> Option<i32> {
+        self.segment(target, jd).map(|s| s.center)
+    }
+
+    /// Segment for a target at a Julian date; later segments take
+    /// precedence, as in SPICE
+    fn segment(&self, target: i32, jd: JulianDate) -> Option<&SpkSegment> {
+        self.segments
+            .iter()
+            .rev()
+            .find(|s| s.target == target && s.covers(jd))
+    }
+
+    /// State of a body relative to its segment center
+    ///
+    /// Returns `None` if no segment covers the body at `jd`, otherwise the
+    /// NAIF ID of the center and the state in km and km/s (J2000 axes).
+    pub fn state(&self, target: i32, jd: JulianDate) -> Result<Option<(i32, State)>> {
+        let segment = match self.segment(target, jd) {
+            Some(segment) => segment,
+            None => return Ok(None),
+        };
+        if segment.frame != J2000_FRAME {
+            return Err(Error::Ephemeris(format!(
+                "Segment '{}' uses frame {}; only J2000 is supported",
+                segment.name, segment.frame
+            )));
+        }
+        let (init, length, size, count) = match segment.directory {
+            Some(directory) if directory.1 > 0.0 && directory.3 > 0 => directory,
+            _ => {
+                return Err(Error::Ephemeris(format!(
+                    "Segment '{}' has unsupported SPK type {}",
+                    segment.name, segment.data_type
+                )))
+            }
+        };
+
+        let components = if segment.data_type == 2 { 3 } else { 6 };
+        let degree = size.saturating_sub(2) / components;
+        if degree == 0 || 2 + components * degree != size {
+            return Err(Error::Ephemeris(format!(
+                "Segment '{}' has an invalid record size of {}",
+                segment.name, size
+            )));
+        }
+
+        let et = (jd.jd - J2000) * DAY_SECONDS;
+        let index = (((et - init) / length).floor().max(0.0) as usize).min(count - 1);
+        let record = self.read_doubles(segment.addresses.0 + index * size, size)?;
+        let (mid, radius) = (record[0], record[1]);
+        let t = (et - mid) / radius;
+
+        let mut values = [0.0; 6];
+        for component in 0..components {
+            let from = 2 + component * degree;
+            let (value, rate) = chebyshev(&record[from..from + degree], t);
+            values[component] = value;
+            if components == 3 {
+                values[3 + component] = rate / radius;
+            }
+        }
+
+        Ok(Some((
+            segment.center,
+            State::new(
+                Position::new(values[0], values[1], values[2]),
+                Position::new(values[3], values[4], values[5]),
+            ),
+        )))
+    }
+
+    /// Read `count` doubles starting at DAF address `address` (1-based)
+    fn read_doubles(&self, address: usize, count: usize) -> Result<Vec<f64>> {
+        let bytes = self.read_bytes((address - 1) * 8, count * 8)?;
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|chunk| {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(chunk);
+                if self.big_endian {
+                    f64::from_be_bytes(word)
+                } else {
+                    f64::from_le_bytes(word)
+                }
+            })
+            .collect())
+    }
+
+    fn read_bytes(&self, offset: usize, count: usize) -> Result<Vec<u8>> {
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| Error::Ephemeris("SPK file handle is poisoned".to_string()))?;
+        let mut bytes = vec![0u8; count];
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Raw bytes of a double in file order
+    fn to_bytes(&self, word: f64) -> [u8; 8] {
+        if self.big_endian {
+            word.to_be_bytes()
+        } else {
+            word.to_le_bytes()
+        }
+    }
+
+    fn int(&self, bytes: &[u8]) -> i32 {
+        let word = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            i32::from_be_bytes(word)
+        } else {
+            i32::from_le_bytes(word)
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Write a little-endian SPK with one type 2 segment per
+    /// `(target, center, scale)`, each of two one-day records from J2000
+    /// with x = scale (1e8 + 1e6 t + 1e3 T2(t)), y = -x, z = scale 5e5 t
+    pub(crate) fn write_kernel(path: &std::path::Path, segments: &[(i32, i32, f64)]) {
+        let pack = |a: i32, b: i32| {
+            let mut word = [0u8; 8];
+            word[..4].copy_from_slice(&a.to_le_bytes());
+            word[4..].copy_from_slice(&b.to_le_bytes());
+            f64::from_le_bytes(word)
+        };
+        let mut bytes = vec![0u8; 3 * RECORD_BYTES];
+        bytes[..8].copy_from_slice(b"DAF/SPK ");
+        bytes[8..12].copy_from_slice(&2i32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&6i32.to_le_bytes());
+        bytes[76..80].copy_from_slice(&2i32.to_le_bytes());
+        bytes[80..84].copy_from_slice(&2i32.to_le_bytes());
+        bytes[88..96].copy_from_slice(b"LTL-IEEE");
+
+        let mut summary = vec![0.0, 0.0, segments.len() as f64];
+        let mut data: Vec<f64> = Vec::new();
+        for (k, &(target, center, scale)) in segments.iter().enumerate() {
+            let first = 3 * RECORD_BYTES / 8 + 1 + data.len();
+            for mid in [43200.0, 129600.0] {
+                data.extend([mid, 43200.0]);
+                let coefficients = [1e8, 1e6, 1e3, -1e8, -1e6, -1e3, 0.0, 5e5, 0.0];
+                data.extend(coefficients.iter().map(|c| c * scale));
+            }
+            data.extend([0.0, 86400.0, 11.0, 2.0]);
+            summary.extend([
+                0.0,
+                2.0 * DAY_SECONDS,
+                pack(target, center),
+                pack(J2000_FRAME, 2),
+                pack(first as i32, (3 * RECORD_BYTES / 8 + data.len()) as i32),
+            ]);
+            let name = 2 * RECORD_BYTES + k * 40;
+            bytes[name..name + 4].copy_from_slice(b"TEST");
+        }
+        for (k, word) in summary.iter().enumerate() {
+            bytes[RECORD_BYTES + k * 8..RECORD_BYTES + (k + 1) * 8]
+                .copy_from_slice(&word.to_le_bytes());
+        }
+        for word in data {
+            bytes.extend(word.to_le_bytes());
+        }
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_type2_segment() {
+        let path = std::env::temp_dir().join(format!("rust_jpl_spk_{}.bsp", std::process::id()));
+        write_kernel(&path, &[(2000001, 10, 1.0)]);
+        let kernel = SpkKernel::open(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let segment = &kernel.segments()[0];
+        assert_eq!((segment.target, segment.center), (2000001, 10));
+        assert_eq!(segment.name, "TEST");
+        assert_eq!(segment.end.jd, J2000 + 2.0);
+
+        // Second record at t = 0.5
+        let jd = JulianDate::new(J2000 + 1.75);
+        let (center, state) = kernel.state(2000001, jd).unwrap().unwrap();
+        assert_eq!(center, 10);
+        let x = 1e8 + 1e6 * 0.5 + 1e3 * (2.0 * 0.25 - 1.0);
+        assert!((state.position.x - x).abs() < 1e-6);
+        assert!((state.position.y + x).abs() < 1e-6);
+        assert!((state.velocity.x - (1e6 + 4e3 * 0.5) / 43200.0).abs() < 1e-9);
+        assert!((state.velocity.z - 5e5 / 43200.0).abs() < 1e-9);
+        assert!(kernel.state(2000002, jd).unwrap().is_none());
+        assert!(!kernel.covers(2000001, JulianDate::new(J2000 + 3.0)));
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        let path =
+            std::env::temp_dir().join(format!("rust_jpl_bad_spk_{}.bsp", std::process::id()));
+        // Begin address of the first summary: zero, then negative
+        let at = RECORD_BYTES + 7 * 8;
+        for address in [0i32, -5] {
+            write_kernel(&path, &[(2000001, 10, 1.0)]);
+            let mut bytes = std::fs::read(&path).unwrap();
+            bytes[at..at + 4].copy_from_slice(&address.to_le_bytes());
+            std::fs::write(&path, bytes).unwrap();
+            let err = SpkKernel::open(path.to_str().unwrap()).err().unwrap();
+            assert!(err.to_string().contains("invalid data addresses"));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_names_the_file() {
+        let path =
+            std::env::temp_dir().join(format!("rust_jpl_not_spk_{}.bsp", std::process::id()));
+        std::fs::write(&path, vec![0u8; RECORD_BYTES]).unwrap();
+        let path = path.to_str().unwrap();
+        let err = SpkKernel::open(path).err().unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(matches!(&err, Error::File { path: file, .. } if file == path));
+        assert!(err.to_string().ends_with("Not a DAF/SPK file"));
+        let missing = SpkKernel::open("no_such_kernel.bsp").err().unwrap();
+        assert!(matches!(missing.root(), Error::Io(_)));
+        assert!(missing.to_string().starts_with("no_such_kernel.bsp: "));
+    }
+}
@@ -0,0 +1,184 @@
+//! Planetary stations and retrograde motion
+//!
+//! A planet is retrograde while its apparent geocentric ecliptic longitude
+//! of date decreases; stations are the instants at which the longitude rate
+//! changes sign.
+
+use crate::apparent;
+use crate::ephemeris::Ephemeris;
+use crate::search::{EventFinder, EventKind, Interval, Relation};
+use crate::time::JulianDate;
+use crate::Result;
+
+/// Sampling step of the station search (days)
+const STATION_STEP: f64 = 1.0;
+
+/// Precision of the returned station times (days, about 1 s)
+const STATION_TOLERANCE: f64 = 1e-5;
+
+/// Half-width of the central difference used for the longitude rate (days)
+const RATE_STEP: f64 = 0.05;
+
+/// Direction of motion after a station
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StationKind {
+    /// The planet turns from direct to retrograde motion
+    Retrograde,
+    /// The planet turns from retrograde to direct motion
+    Direct,
+}
+
+/// Instant at which a planet's longitude rate changes sign
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Station {
+    /// Type of station
+    pub kind: StationKind,
+    /// Time of the station (TDB)
+    pub jd: JulianDate,
+    /// Apparent ecliptic longitude at the station (degrees)
+    pub longitude: f64,
+}
+
+/// Rate of change of the apparent geocentric ecliptic longitude of a body
+/// (degrees per day, negative while retrograde)
+///
+/// The difference becomes one-sided near the ends of the ephemeris, so that
+/// searches may run up to its bounds.
+pub fn longitude_rate(eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<f64> {
+    let (first, last) = eph.get_date_range();
+    let (behind, ahead) = (
+        (jd.jd - RATE_STEP).max(first),
+        (jd.jd + RATE_STEP).min(last),
+    );
+    let ahead_longitude = apparent::ecliptic_of_date(eph, body_name, JulianDate::new(ahead))?;
+    let behind_longitude = apparent::ecliptic_of_date(eph, body_name, JulianDate::new(behind))?;
+    let change =
+        (ahead_longitude.longitude - behind_longitude.longitude + 180.0).rem_euclid(360.0) - 180.0;
+    Ok(change / (ahead - behind))
+}
+
+/// Whether a body is in apparent retrograde motion
+pub fn is_retrograde(eph: &Ephemeris, body_name: &str, jd: JulianDate) -> Result<bool> {
+    Ok(longitude_rate(eph, body_name, jd)? < 0.0)
+}
+
+/// Find the retrograde and direct stations of a body between two dates
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::stations::find_stations;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let eph = Ephemeris::new("config.toml")?;
+/// let start = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0)?;
+/// let end = JulianDate::from_calendar(2025, 1, 1, 0, 0, 0.0)?;
+/// for station in find_stations(&eph, "Mercury", start, end)? {
+///     println!("{:?} station at {:.2} degrees", station.kind, station.longitude);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn find_stations(
+    eph: &Ephemeris,
+    body_name: &str,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<Station>> {
+    let rate = |jd: JulianDate| longitude_rate(eph, body_name, jd);
+
+    let mut stations = Vec::new();
+    for root in finder(eph).roots(rate, start, end)? {
+        let kind = if root.kind == EventKind::Falling {
+            StationKind::Retrograde
+        } else {
+            StationKind::Direct
+        };
+        stations.push(Station {
+            kind,
+            jd: root.jd,
+            longitude: apparent::ecliptic_of_date(eph, body_name, root.jd)?.longitude,
+        });
+    }
+    Ok(stations)
+}
+
+/// Find the intervals in which a body is retrograde
+///
+/// Intervals in progress at the ends of the window are clipped to it.
+pub fn find_retrograde_periods(
+    eph: &Ephemeris,
+    body_name: &str,
+    start: JulianDate,
+    end: JulianDate,
+) -> Result<Vec<Interval>> {
+    finder(eph).windows(
+        |jd| longitude_rate(eph, body_name, jd),
+        Relation::Below(0.0),
+        start,
+        end,
+    )
+}
+
+fn finder(eph: &Ephemeris) -> EventFinder {
+    EventFinder::for_ephemeris(eph)
+        .with_step(STATION_STEP)
+        .with_tolerance(STATION_TOLERANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::{model, MODEL_EMB, MODEL_MARS, MODEL_VENUS};
+
+    #[test]
+    fn test_find_stations_up_to_the_ephemeris_end() {
+        let eph = model();
+        let (_, last) = eph.get_date_range();
+        let (start, end) = (JulianDate::new(2452000.0), JulianDate::new(last));
+        let stations = find_stations(&eph, "Mars", start, end).unwrap();
+        let kinds: Vec<StationKind> = stations.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, [StationKind::Retrograde, StationKind::Direct]);
+        assert!(longitude_rate(&eph, "Mars", end).unwrap() > 0.0);
+
+        // Circular coplanar orbits are stationary when the heliocentric angle
+        // between the planets has cosine (w1 r1^2 + w2 r2^2) / (r1 r2 (w1 + w2))
+        let (w1, w2) = (360.0 / MODEL_EMB.period, 360.0 / MODEL_MARS.period);
+        let (r1, r2) = (MODEL_EMB.a, MODEL_MARS.a);
+        let angle = ((w1 * r1 * r1 + w2 * r2 * r2) / (r1 * r2 * (w1 + w2)))
+            .acos()
+            .to_degrees();
+        let lead = |jd: f64| MODEL_MARS.m0 - MODEL_EMB.m0 + (w2 - w1) * (jd - 2451545.0);
+        for (station, expected) in stations.iter().zip([angle, -angle]) {
+            let residual = (lead(station.jd.jd) - expected + 180.0).rem_euclid(360.0) - 180.0;
+            assert!((residual / (w1 - w2)).abs() < 0.2);
+        }
+
+        let periods = find_retrograde_periods(&eph, "Mars", start, end).unwrap();
+        assert_eq!(periods.len(), 1);
+        assert!((periods[0].start.jd - stations[0].jd.jd).abs() < 1e-4);
+        assert!((periods[0].end.jd - stations[1].jd.jd).abs() < 1e-4);
+        let middle = JulianDate::new(0.5 * (periods[0].start.jd + periods[0].end.jd));
+        assert!(is_retrograde(&eph, "Mars", middle).unwrap());
+    }
+
+    #[test]
+    fn test_find_stations_from_the_ephemeris_start() {
+        let eph = model();
+        let (first, _) = eph.get_date_range();
+        let (start, end) = (JulianDate::new(first), JulianDate::new(2452100.0));
+        assert!(longitude_rate(&eph, "Venus", start).unwrap() > 0.0);
+        let stations = find_stations(&eph, "Venus", start, end).unwrap();
+        let kinds: Vec<StationKind> = stations.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, [StationKind::Retrograde, StationKind::Direct]);
+
+        // Venus leads the Earth by the station angle, then trails it by it
+        let (w1, w2) = (360.0 / MODEL_VENUS.period, 360.0 / MODEL_EMB.period);
+        let (r1, r2) = (MODEL_VENUS.a, MODEL_EMB.a);
+        let angle = ((w1 * r1 * r1 + w2 * r2 * r2) / (r1 * r2 * (w1 + w2)))
+            .acos()
+            .to_degrees();
+        let lead = |jd: f64| MODEL_VENUS.m0 - MODEL_EMB.m0 + (w1 - w2) * (jd - 2451545.0);
+        for (station, expected) in stations.iter().zip([-angle, angle]) {
+            let residual = (lead(station.jd.jd) - expected + 180.0).rem_euclid(360.0) - 180.0;
+            assert!((residual / (w1 - w2)).abs() < 0.2);
+        }
+    }
+}
@@ -1,6 +1,10 @@
 //! Time conversion utilities for Julian dates and calendar dates
 
-use crate::Error;
+use std::f64::consts::TAU;
+
+use crate::ephemeris::Ephemeris;
+use crate::frames::{self, ARCSEC, J2000};
+use crate::{Error, Result};
 
 /// Represents a Julian Date (JD)
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -17,6 +21,10 @@ impl JulianDate {
 
     /// Convert calendar date to Julian date
     ///
+    /// Dates from 1582 October 15 are Gregorian and earlier ones Julian, as
+    /// in Meeus, Astronomical Algorithms, chapter 7. Years are astronomical:
+    /// year 0 is 1 BC, so JD 0 is -4712 January 1 at 12h.
+    ///
     /// # Arguments
     /// * `year` - Year (e.g., 2024)
     /// * `month` - Month (1-12)
@@ -37,7 +45,7 @@ impl JulianDate {
         hour: i32,
         minute: i32,
         second: f64,
-    ) -> Result<Self, Error> {
+    ) -> Result<Self> {
         if !(1..=12).contains(&month) {
             return Err(Error::InvalidDate(format!("Invalid month: {}", month)));
         }
@@ -54,42 +62,48 @@ impl JulianDate {
             return Err(Error::InvalidDate(format!("Invalid second: {}", second)));
         }
 
-        let a = (14 - month) / 12;
-        let y = year + 4800 - a;
-        let m = month + 12 * a - 3;
-
-        let jdn = day as f64 + (153 * m + 2) as f64 / 5.0 + 365.0 * y as f64 + (y / 4) as f64
-            - (y / 100) as f64
-            + (y / 400) as f64
-            - 32045.0;
+        // Floor division throughout, so that years before 0 and dates
+        // before JD 0 are not shifted by a day
+        let (y, m) = if month <= 2 {
+            (year as i64 - 1, month as i64 + 12)
+        } else {
+            (year as i64, month as i64)
+        };
+        let b = if (year, month, day) >= (1582, 10, 15) {
+            let a = y.div_euclid(100);
+            2 - a + a.div_euclid(4)
+        } else {
+            0
+        };
+        let jdn = (1461 * (y + 4716)).div_euclid(4) + 306 * (m + 1) / 10 + day as i64 + b - 1524;
 
         let fraction = (hour as f64 + minute as f64 / 60.0 + second / 3600.0) / 24.0;
 
         Ok(Self {
-            jd: jdn + fraction - 0.5,
+            jd: jdn as f64 + fraction - 0.5,
         })
     }
 
-    /// Convert Julian date to calendar date
+    /// Convert Julian date to calendar date, Gregorian from 1582 October 15
+    /// and Julian before (see [`from_calendar`](Self::from_calendar))
     pub fn to_calendar(&self) -> CalendarDate {
-        let j = (self.jd + 0.5).floor() as i64;
-        let f = self.jd + 0.5 - j as f64;
-
-        let j0 = j;
-        let j1 = j0 + 68569;
-        let j2 = 4 * j1 / 146097;
-        let j3 = j1 - (146097 * j2 + 3) / 4;
-        let j4 = 4000 * (j3 + 1) / 1461001;
-        let j5 = j3 - 1461 * j4 / 4 + 31;
-        let j6 = 80 * j5 / 2447;
-        let j7 = j5 - 2447 * j6 / 80;
-        let j8 = j6 / 11;
-        let j9 = j6 + 2 - 12 * j8;
-        let j10 = 100 * (j2 - 49) + j4 + j8;
-
-        let year = j10 as i32;
-        let month = j9 as i32;
-        let day = j7 as i32;
+        let z = (self.jd + 0.5).floor() as i64;
+        let f = self.jd + 0.5 - z as f64;
+
+        let a = if z < 2299161 {
+            z
+        } else {
+            let alpha = (4 * z - 7468865).div_euclid(146097);
+            z + 1 + alpha - alpha.div_euclid(4)
+        };
+        let b = a + 1524;
+        let c = (100 * b - 12210).div_euclid(36525);
+        let d = (1461 * c).div_euclid(4);
+        let e = 10000 * (b - d) / 306001;
+
+        let day = (b - d - 306001 * e / 10000) as i32;
+        let month = (if e < 14 { e - 1 } else { e - 13 }) as i32;
+        let year = (if month > 2 { c - 4716 } else { c - 4715 }) as i32;
 
         let total_seconds = f * 86400.0;
         let hour = (total_seconds / 3600.0) as i32;
@@ -112,6 +126,260 @@ impl JulianDate {
     }
 }
 
+/// Difference TT - UT1 in seconds (Espenak & Meeus polynomial model)
+///
+/// The model follows the observed values from 1600 to the present and
+/// the long-term parabolic extrapolation outside that range.
+pub fn delta_t(jd: JulianDate) -> f64 {
+    let y = 2000.0 + (jd.jd - 2451544.5) / 365.2425;
+    let parabola = |y: f64| {
+        let u = (y - 1820.0) / 100.0;
+        -20.0 + 32.0 * u * u
+    };
+
+    if y < -500.0 {
+        parabola(y)
+    } else if y < 500.0 {
+        let u = y / 100.0;
+        10583.6
+            + u * (-1014.41
+                + u * (33.78311
+                    + u * (-5.952053 + u * (-0.1798452 + u * (0.022174192 + u * 0.0090316521)))))
+    } else if y < 1600.0 {
+        let u = (y - 1000.0) / 100.0;
+        1574.2
+            + u * (-556.01
+                + u * (71.23472
+                    + u * (0.319781 + u * (-0.8503463 + u * (-0.005050998 + u * 0.0083572073)))))
+    } else if y < 1700.0 {
+        let t = y - 1600.0;
+        120.0 + t * (-0.9808 + t * (-0.01532 + t / 7129.0))
+    } else if y < 1800.0 {
+        let t = y - 1700.0;
+        8.83 + t * (0.1603 + t * (-0.0059285 + t * (0.00013336 - t / 1174000.0)))
+    } else if y < 1860.0 {
+        let t = y - 1800.0;
+        13.72
+            + t * (-0.332447
+                + t * (0.0068612
+                    + t * (0.0041116
+                        + t * (-0.00037436
+                            + t * (0.0000121272 + t * (-0.0000001699 + t * 0.000000000875))))))
+    } else if y < 1900.0 {
+        let t = y - 1860.0;
+        7.62 + t
+            * (0.5737 + t * (-0.251754 + t * (0.01680668 + t * (-0.0004473624 + t / 233174.0))))
+    } else if y < 1920.0 {
+        let t = y - 1900.0;
+        -2.79 + t * (1.494119 + t * (-0.0598939 + t * (0.0061966 - t * 0.000197)))
+    } else if y < 1941.0 {
+        let t = y - 1920.0;
+        21.20 + t * (0.84493 + t * (-0.076100 + t * 0.0020936))
+    } else if y < 1961.0 {
+        let t = y - 1950.0;
+        29.07 + t * (0.407 + t * (-1.0 / 233.0 + t / 2547.0))
+    } else if y < 1986.0 {
+        let t = y - 1975.0;
+        45.45 + t * (1.067 + t * (-1.0 / 260.0 - t / 718.0))
+    } else if y < 2005.0 {
+        let t = y - 2000.0;
+        63.86
+            + t * (0.3345
+                + t * (-0.060374 + t * (0.0017275 + t * (0.000651814 + t * 0.00002373599))))
+    } else if y < 2050.0 {
+        let t = y - 2000.0;
+        62.92 + t * (0.32217 + t * 0.005589)
+    } else if y < 2150.0 {
+        parabola(y) - 0.5628 * (2150.0 - y)
+    } else {
+        parabola(y)
+    }
+}
+
+/// Convert a TT Julian date to UT1 using the Delta T model
+pub fn tt_to_ut1(jd_tt: JulianDate) -> JulianDate {
+    JulianDate::new(jd_tt.jd - delta_t(jd_tt) / 86400.0)
+}
+
+/// Convert a UT1 Julian date to TT using the Delta T model
+pub fn ut1_to_tt(jd_ut1: JulianDate) -> JulianDate {
+    let guess = JulianDate::new(jd_ut1.jd + delta_t(jd_ut1) / 86400.0);
+    JulianDate::new(jd_ut1.jd + delta_t(guess) / 86400.0)
+}
+
+/// Difference TT - TAI in seconds
+const TT_MINUS_TAI: f64 = 32.184;
+
+/// Leap seconds: (year, month, TAI - UTC from the first day of that month)
+const LEAP_SECONDS: &[(i32, i32, f64)] = &[
+    (1972, 1, 10.0),
+    (1972, 7, 11.0),
+    (1973, 1, 12.0),
+    (1974, 1, 13.0),
+    (1975, 1, 14.0),
+    (1976, 1, 15.0),
+    (1977, 1, 16.0),
+    (1978, 1, 17.0),
+    (1979, 1, 18.0),
+    (1980, 1, 19.0),
+    (1981, 7, 20.0),
+    (1982, 7, 21.0),
+    (1983, 7, 22.0),
+    (1985, 7, 23.0),
+    (1988, 1, 24.0),
+    (1990, 1, 25.0),
+    (1991, 1, 26.0),
+    (1992, 7, 27.0),
+    (1993, 7, 28.0),
+    (1994, 7, 29.0),
+    (1996, 1, 30.0),
+    (1997, 7, 31.0),
+    (1999, 1, 32.0),
+    (2006, 1, 33.0),
+    (2009, 1, 34.0),
+    (2012, 7, 35.0),
+    (2015, 7, 36.0),
+    (2017, 1, 37.0),
+];
+
+/// TAI - UTC in seconds for a UTC Julian date, or `None` before 1972
+///
+/// The last tabulated value is held constant after the final leap second.
+pub fn tai_minus_utc(jd_utc: JulianDate) -> Option<f64> {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|&&(year, month, _)| {
+            JulianDate::from_calendar(year, month, 1, 0, 0, 0.0)
+                .is_ok_and(|start| jd_utc.jd >= start.jd)
+        })
+        .map(|&(_, _, offset)| offset)
+}
+
+/// Difference TDB - TT in seconds (principal periodic terms, ~10 µs)
+pub fn tdb_minus_tt(jd: JulianDate) -> f64 {
+    let g = (357.53 + 0.98560028 * (jd.jd - J2000)).to_radians();
+    0.001657 * g.sin() + 0.000014 * (2.0 * g).sin()
+}
+
+/// Convert a TDB Julian date to TT
+pub fn tdb_to_tt(jd_tdb: JulianDate) -> JulianDate {
+    JulianDate::new(jd_tdb.jd - tdb_minus_tt(jd_tdb) / 86400.0)
+}
+
+/// Convert a TT Julian date to TDB
+pub fn tt_to_tdb(jd_tt: JulianDate) -> JulianDate {
+    JulianDate::new(jd_tt.jd + tdb_minus_tt(jd_tt) / 86400.0)
+}
+
+/// Convert a UTC Julian date to TT
+///
+/// From 1972 the leap second table is used; earlier dates have no UTC
+/// and are treated as UT1 through the Delta T model.
+pub fn utc_to_tt(jd_utc: JulianDate) -> JulianDate {
+    match tai_minus_utc(jd_utc) {
+        Some(leap) => JulianDate::new(jd_utc.jd + (leap + TT_MINUS_TAI) / 86400.0),
+        None => ut1_to_tt(jd_utc),
+    }
+}
+
+/// Convert a TT Julian date to UTC (see [`utc_to_tt`] for the model)
+pub fn tt_to_utc(jd_tt: JulianDate) -> JulianDate {
+    let guess = JulianDate::new(jd_tt.jd - (37.0 + TT_MINUS_TAI) / 86400.0);
+    match tai_minus_utc(guess) {
+        Some(leap) => {
+            let utc = JulianDate::new(jd_tt.jd - (leap + TT_MINUS_TAI) / 86400.0);
+            match tai_minus_utc(utc) {
+                Some(leap) => JulianDate::new(jd_tt.jd - (leap + TT_MINUS_TAI) / 86400.0),
+                None => tt_to_ut1(jd_tt),
+            }
+        }
+        None => tt_to_ut1(jd_tt),
+    }
+}
+
+/// Convert a TDB Julian date to UTC
+pub fn tdb_to_utc(jd_tdb: JulianDate) -> JulianDate {
+    tt_to_utc(tdb_to_tt(jd_tdb))
+}
+
+/// Convert a UTC Julian date to TDB
+pub fn utc_to_tdb(jd_utc: JulianDate) -> JulianDate {
+    tt_to_tdb(utc_to_tt(jd_utc))
+}
+
+/// Earth rotation angle (radians, 0-2π) for a UT1 Julian date (IAU 2000)
+pub fn earth_rotation_angle(jd_ut1: JulianDate) -> f64 {
+    let d = jd_ut1.jd - J2000;
+    (TAU * (d.rem_euclid(1.0) + 0.7790572732640 + 0.00273781191135448 * d)).rem_euclid(TAU)
+}
+
+/// Greenwich mean sidereal time (radians, 0-2π), IAU 2006
+///
+/// # Arguments
+/// * `jd_ut1` - Julian date (UT1), drives the Earth rotation angle
+/// * `jd_tt` - Julian date (TT), drives the precession polynomial
+pub fn greenwich_mean_sidereal_time(jd_ut1: JulianDate, jd_tt: JulianDate) -> f64 {
+    let t = frames::centuries_since_j2000(jd_tt.jd);
+    let polynomial = 0.014506
+        + t * (4612.156534
+            + t * (1.3915817 + t * (-0.00000044 + t * (-0.000029956 + t * -0.0000000368))));
+    (earth_rotation_angle(jd_ut1) + polynomial * ARCSEC).rem_euclid(TAU)
+}
+
+/// Equation of the equinoxes (radians) for a given nutation in longitude,
+/// including the principal complementary terms
+pub fn equation_of_equinoxes(jd_tt: JulianDate, dpsi: f64) -> f64 {
+    let t = frames::centuries_since_j2000(jd_tt.jd);
+    let omega = (125.04452 - 1934.136261 * t).to_radians();
+    dpsi * frames::mean_obliquity(jd_tt.jd).cos()
+        + (0.00264096 * omega.sin() + 0.00006352 * (2.0 * omega).sin()) * ARCSEC
+}
+
+/// Greenwich apparent sidereal time (radians, 0-2π), using the truncated
+/// IAU 1980 nutation series of [`frames::nutation`]
+///
+/// The nutation model limits the agreement with IAU 2000A to a few
+/// milliseconds of time near J2000.
+///
+/// # Example
+/// ```
+/// use rust_jpl::time::{greenwich_apparent_sidereal_time, ut1_to_tt};
+/// use rust_jpl::JulianDate;
+/// let ut1 = JulianDate::from_calendar(1987, 4, 10, 0, 0, 0.0)?;
+/// let gast = greenwich_apparent_sidereal_time(ut1, ut1_to_tt(ut1));
+/// assert!((gast.to_degrees() / 15.0 - 13.1795).abs() < 1e-3);
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn greenwich_apparent_sidereal_time(jd_ut1: JulianDate, jd_tt: JulianDate) -> f64 {
+    let (dpsi, _) = frames::nutation(jd_tt.jd);
+    (greenwich_mean_sidereal_time(jd_ut1, jd_tt) + equation_of_equinoxes(jd_tt, dpsi))
+        .rem_euclid(TAU)
+}
+
+/// Greenwich apparent sidereal time (radians, 0-2π), taking the nutation
+/// in longitude from the ephemeris when it carries nutation angles
+pub fn greenwich_apparent_sidereal_time_with(
+    eph: &Ephemeris,
+    jd_ut1: JulianDate,
+    jd_tt: JulianDate,
+) -> Result<f64> {
+    let (dpsi, _) = frames::nutation_angles(eph, jd_tt)?;
+    Ok(
+        (greenwich_mean_sidereal_time(jd_ut1, jd_tt) + equation_of_equinoxes(jd_tt, dpsi))
+            .rem_euclid(TAU),
+    )
+}
+
+/// Local sidereal time (radians, 0-2π) from a Greenwich sidereal time
+///
+/// # Arguments
+/// * `greenwich` - Greenwich mean or apparent sidereal time (radians)
+/// * `longitude` - Observer longitude in degrees, east positive
+pub fn local_sidereal_time(greenwich: f64, longitude: f64) -> f64 {
+    (greenwich + longitude.to_radians()).rem_euclid(TAU)
+}
+
 /// Represents a calendar date
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct CalendarDate {
@@ -137,7 +405,7 @@ impl CalendarDate {
     }
 
     /// Convert to Julian date
-    pub fn to_julian(&self) -> Result<JulianDate, Error> {
+    pub fn to_julian(&self) -> Result<JulianDate> {
         JulianDate::from_calendar(
             self.year,
             self.month,
@@ -153,12 +421,97 @@ impl CalendarDate {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sidereal_time() {
+        // Meeus, Astronomical Algorithms, example 12.a: 1987 April 10, 0h UT
+        let ut1 = JulianDate::new(2446895.5);
+        let tt = ut1_to_tt(ut1);
+        let gmst = greenwich_mean_sidereal_time(ut1, tt).to_degrees() / 15.0;
+        let gast = greenwich_apparent_sidereal_time(ut1, tt).to_degrees() / 15.0;
+        assert!((gmst - (13.0 + 10.0 / 60.0 + 46.3668 / 3600.0)).abs() * 3600.0 < 0.01);
+        assert!((gast - (13.0 + 10.0 / 60.0 + 46.1351 / 3600.0)).abs() * 3600.0 < 0.01);
+        assert!((local_sidereal_time(0.0, -90.0).to_degrees() - 270.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_utc_conversion() {
+        let utc = JulianDate::from_calendar(2024, 1, 1, 0, 0, 0.0).unwrap();
+        let tt = utc_to_tt(utc);
+        assert!(((tt.jd - utc.jd) * 86400.0 - 69.184).abs() < 1e-4);
+        assert!((tt_to_utc(tt).jd - utc.jd).abs() * 86400.0 < 1e-4);
+        assert_eq!(tai_minus_utc(JulianDate::new(2440000.0)), None);
+    }
+
     #[test]
     fn test_julian_date_conversion() {
+        let j2000 = JulianDate::from_calendar(2000, 1, 1, 12, 0, 0.0).unwrap();
+        assert_eq!(j2000.jd, 2451545.0);
+
         let jd = JulianDate::from_calendar(2024, 1, 15, 12, 0, 0.0).unwrap();
         let cal = jd.to_calendar();
         assert_eq!(cal.year, 2024);
         assert_eq!(cal.month, 1);
         assert_eq!(cal.day, 15);
     }
+
+    #[test]
+    fn test_calendar_before_year_zero() {
+        // Meeus, Astronomical Algorithms, chapter 7, and the Julian calendar
+        // reform of 1582
+        let dates = [
+            ((-4712, 1, 1, 12), 0.0),
+            ((-4713, 12, 31, 12), -1.0),
+            ((-1000, 7, 12, 12), 1356001.0),
+            ((-1000, 2, 29, 0), 1355866.5),
+            ((-584, 5, 28, 12), 1507900.0),
+            ((333, 1, 27, 12), 1842713.0),
+            ((1582, 10, 4, 0), 2299159.5),
+            ((1582, 10, 15, 0), 2299160.5),
+            ((-10000, 1, 1, 0), -1931442.5),
+            ((-13200, 3, 1, 6), -3100182.25),
+        ];
+        for ((year, month, day, hour), expected) in dates {
+            let jd = JulianDate::from_calendar(year, month, day, hour, 0, 0.0).unwrap();
+            assert_eq!(jd.jd, expected, "{}-{}-{}", year, month, day);
+            let cal = jd.to_calendar();
+            assert_eq!(
+                (cal.year, cal.month, cal.day, cal.hour),
+                (year, month, day, hour)
+            );
+        }
+
+        // Whole days either side of JD 0 and of the reform
+        for jd in (-40..40)
+            .map(|k| k as f64 * 0.5)
+            .chain([2299159.5, 2299160.5])
+        {
+            let cal = JulianDate::new(jd).to_calendar();
+            assert_eq!(cal.to_julian().unwrap().jd, jd);
+        }
+    }
+
+    #[test]
+    fn test_from_calendar_day_of_year() {
+        // Meeus, Astronomical Algorithms, chapter 7; the day-of-year term
+        // must be truncated before it is added
+        let dates = [
+            ((1957, 10, 4, 19, 26, 24.0), 2436116.31),
+            ((1987, 4, 10, 0, 0, 0.0), 2446895.5),
+            ((1988, 6, 19, 12, 0, 0.0), 2447332.0),
+            ((1999, 1, 1, 0, 0, 0.0), 2451179.5),
+            ((2024, 3, 1, 0, 0, 0.0), 2460370.5),
+        ];
+        for ((year, month, day, hour, minute, second), expected) in dates {
+            let jd = JulianDate::from_calendar(year, month, day, hour, minute, second).unwrap();
+            assert!(
+                (jd.jd - expected).abs() < 1e-8,
+                "{}-{}-{}",
+                year,
+                month,
+                day
+            );
+            let cal = jd.to_calendar();
+            assert_eq!((cal.year, cal.month, cal.day), (year, month, day));
+        }
+    }
 }
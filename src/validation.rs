@@ -0,0 +1,471 @@
+//! Validation against JPL test points (`testpo.nnn`)
+//!
+//! JPL ships each DE with a test-point file of values computed by its own
+//! reader. Each line after `EOT` gives a date, a target, a center, a
+//! coordinate number and the expected value; positions are in AU, velocities
+//! in AU/day, angles in radians and TT-TDB in seconds.
+
+use std::fmt;
+
+use crate::ephemeris::{Ephemeris, Position, State};
+use crate::time::JulianDate;
+use crate::{Error, FileKind, Result};
+
+/// Largest deviation accepted by JPL's `testeph` program
+pub const TOLERANCE: f64 = 1e-13;
+
+/// Names of the test-point bodies, numbered from 1
+const TARGETS: [&str; 17] = [
+    "Mercury",
+    "Venus",
+    "Earth",
+    "Mars",
+    "Jupiter",
+    "Saturn",
+    "Uranus",
+    "Neptune",
+    "Pluto",
+    "Moon",
+    "Sun",
+    "Solar_system_barycenter",
+    "EarthMoon_barycenter",
+    "Earth_Nutations",
+    "Lunar_mantle_libration",
+    "Lunar_mantle_omega",
+    "TT_TDB",
+];
+
+/// First test-point number of the quantities that have no center
+const FIRST_ANGLE_TARGET: u32 = 14;
+
+/// One line of a test-point file
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestPoint {
+    /// DE number the value was computed from
+    pub denum: i32,
+    /// Julian date (TDB)
+    pub jd: f64,
+    /// Target number (1 Mercury ... 13 Earth-Moon barycenter, 14 nutations,
+    /// 15 librations, 16 lunar mantle rates, 17 TT-TDB)
+    pub target: u32,
+    /// Center number, 0 when the target has no center
+    pub center: u32,
+    /// Coordinate number from 1 (x, y, z, then their rates)
+    pub coordinate: usize,
+    /// Expected value
+    pub value: f64,
+}
+
+impl TestPoint {
+    /// Parse line `number` (1-based) of a test point file, a data line such as
+    /// `441  1950.01.01 2433282.5  1  3  1  -0.3713...`
+    pub fn parse(number: usize, line: &str) -> Result<Self> {
+        let invalid = || Error::Parse {
+            kind: FileKind::TestPoints,
+            line: number,
+            group: String::new(),
+            message: format!("invalid test point '{}'", line.trim()),
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() != 7 {
+            return Err(invalid());
+        }
+
+        let point = Self {
+            denum: tokens[0].parse().map_err(|_| invalid())?,
+            jd: tokens[2].parse().map_err(|_| invalid())?,
+            target: tokens[3].parse().map_err(|_| invalid())?,
+            center: tokens[4].parse().map_err(|_| invalid())?,
+            coordinate: tokens[5].parse().map_err(|_| invalid())?,
+            value: tokens[6]
+                .replace(['D', 'd'], "E")
+                .parse()
+                .map_err(|_| invalid())?,
+        };
+        let known = |n: u32| (1..=TARGETS.len() as u32).contains(&n);
+        if !known(point.target)
+            || (point.center != 0 && !known(point.center))
+            || !(1..=6).contains(&point.coordinate)
+        {
+            return Err(invalid());
+        }
+        Ok(point)
+    }
+
+    /// Name of the target body or quantity
+    pub fn target_name(&self) -> &'static str {
+        TARGETS[self.target as usize - 1]
+    }
+}
+
+/// Read the test points of a file, which follow a line holding `EOT`
+pub fn read_test_points(path: &str) -> Result<Vec<TestPoint>> {
+    let text = std::fs::read_to_string(path).map_err(|e| Error::from(e).in_file(path))?;
+    let mut lines = text.lines().enumerate().map(|(k, line)| (k + 1, line));
+    if !lines.by_ref().any(|(_, line)| line.trim() == "EOT") {
+        return Err(Error::Config(format!(
+            "Test point file {} has no EOT line",
+            path
+        )));
+    }
+    lines
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| TestPoint::parse(number, line).map_err(|e| e.in_file(path)))
+        .collect()
+}
+
+/// Largest deviation found for one target
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyDeviation {
+    /// Target number
+    pub target: u32,
+    /// Target name
+    pub name: &'static str,
+    /// Number of test points evaluated
+    pub points: usize,
+    /// Largest absolute deviation from the expected values
+    pub max_deviation: f64,
+    /// Test point with the largest deviation
+    pub worst: TestPoint,
+}
+
+impl BodyDeviation {
+    /// Whether the largest deviation is within a tolerance
+    pub fn passed(&self, tolerance: f64) -> bool {
+        self.max_deviation <= tolerance
+    }
+}
+
+/// Result of evaluating a set of test points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verification {
+    /// Largest deviation of each target, ordered by target number
+    pub bodies: Vec<BodyDeviation>,
+    /// Tolerance the deviations are judged against
+    pub tolerance: f64,
+    /// Test points outside the date range or bodies of the ephemeris
+    pub skipped: usize,
+}
+
+impl Verification {
+    /// Whether every evaluated target is within the tolerance
+    pub fn passed(&self) -> bool {
+        self.bodies.iter().all(|b| b.passed(self.tolerance))
+    }
+
+    /// Targets whose largest deviation exceeds the tolerance
+    pub fn failures(&self) -> Vec<&BodyDeviation> {
+        self.bodies
+            .iter()
+            .filter(|b| !b.passed(self.tolerance))
+            .collect()
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>7} {:>12}  result",
+            "target", "points", "max dev"
+        )?;
+        for body in &self.bodies {
+            writeln!(
+                f,
+                "{:<24} {:>7} {:>12.3e}  {}",
+                body.name,
+                body.points,
+                body.max_deviation,
+                if body.passed(self.tolerance) {
+                    "ok"
+                } else {
+                    "FAIL"
+                }
+            )?;
+        }
+        write!(
+            f,
+            "tolerance {:e}, {} points skipped",
+            self.tolerance, self.skipped
+        )
+    }
+}
+
+/// Evaluate test points and report the largest deviation of each target
+///
+/// Points outside the date range of the ephemeris, or for bodies it does
+/// not carry, are counted as skipped.
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::validation::{read_test_points, verify, TOLERANCE};
+/// use rust_jpl::Ephemeris;
+/// let eph = Ephemeris::new("config.toml")?;
+/// let points = read_test_points("assets/testpo.441")?;
+/// let report = verify(&eph, &points, TOLERANCE)?;
+/// println!("{}", report);
+/// assert!(report.passed());
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn verify(eph: &Ephemeris, points: &[TestPoint], tolerance: f64) -> Result<Verification> {
+    let (start, end) = eph.get_date_range();
+    let mut bodies: Vec<BodyDeviation> = Vec::new();
+    let mut skipped = 0;
+
+    for point in points {
+        let jd = JulianDate::new(point.jd);
+        let value = if point.jd < start || point.jd > end {
+            None
+        } else {
+            evaluate(eph, point, jd)?
+        };
+        let value = match value {
+            Some(value) => value,
+            None => {
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let mut deviation = (value - point.value).abs();
+        // testeph scales the libration angle psi, which grows steadily with
+        // time, by its approximate rate
+        if point.target == 15 && point.coordinate == 3 {
+            deviation /= (0.23 * (point.jd - 2451545.0)).abs().max(1.0);
+        }
+
+        match bodies.iter_mut().find(|b| b.target == point.target) {
+            Some(body) => {
+                body.points += 1;
+                if deviation > body.max_deviation {
+                    body.max_deviation = deviation;
+                    body.worst = point.clone();
+                }
+            }
+            None => bodies.push(BodyDeviation {
+                target: point.target,
+                name: point.target_name(),
+                points: 1,
+                max_deviation: deviation,
+                worst: point.clone(),
+            }),
+        }
+    }
+
+    bodies.sort_by_key(|b| b.target);
+    Ok(Verification {
+        bodies,
+        tolerance,
+        skipped,
+    })
+}
+
+/// Evaluate the test points of a file
+pub fn verify_file(eph: &Ephemeris, path: &str, tolerance: f64) -> Result<Verification> {
+    verify(eph, &read_test_points(path)?, tolerance)
+}
+
+/// Largest position difference of one body between two ephemerides
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Body name
+    pub name: String,
+    /// Number of dates compared
+    pub samples: usize,
+    /// Largest distance between the two positions (km)
+    pub max_km: f64,
+    /// Julian date of the largest distance
+    pub worst_jd: f64,
+}
+
+/// Compare the positions of some bodies in two ephemerides every `step`
+/// days from `start` to `end`
+///
+/// This measures the accuracy of a trimmed copy, such as one written with
+/// [`Subset::with_tolerance`](crate::export::Subset::with_tolerance),
+/// against the full ephemeris it was made from.
+///
+/// # Example
+/// ```ignore
+/// use rust_jpl::validation::compare;
+/// use rust_jpl::{Ephemeris, JulianDate};
+/// let full = Ephemeris::open("assets/linux_p1550p2650.440")?;
+/// let compact = Ephemeris::open("assets/de440_compact.bin")?;
+/// let (start, end) = compact.get_date_range();
+/// for body in compare(&full, &compact, &["Mars", "Moon"], start, end, 0.5)? {
+///     println!("{}: {:.3} km", body.name, body.max_km);
+/// }
+/// # Ok::<(), rust_jpl::Error>(())
+/// ```
+pub fn compare(
+    reference: &Ephemeris,
+    candidate: &Ephemeris,
+    bodies: &[&str],
+    start: f64,
+    end: f64,
+    step: f64,
+) -> Result<Vec<Comparison>> {
+    if step <= 0.0 || end < start {
+        return Err(Error::InvalidDate(format!(
+            "Cannot compare [{}, {}] every {} days",
+            start, end, step
+        )));
+    }
+    let samples = ((end - start) / step).floor() as usize + 1;
+    let au = reference.au();
+
+    bodies
+        .iter()
+        .map(|&name| {
+            let mut comparison = Comparison {
+                name: name.to_string(),
+                samples,
+                max_km: 0.0,
+                worst_jd: start,
+            };
+            for k in 0..samples {
+                let jd = JulianDate::new(start + k as f64 * step);
+                let difference =
+                    reference.get_position(name, jd)? - candidate.get_position(name, jd)?;
+                let km = difference.distance() * au;
+                if km > comparison.max_km {
+                    comparison.max_km = km;
+                    comparison.worst_jd = jd.as_f64();
+                }
+            }
+            Ok(comparison)
+        })
+        .collect()
+}
+
+/// Value of a test point's coordinate, or `None` if the ephemeris does not
+/// carry the bodies involved
+fn evaluate(eph: &Ephemeris, point: &TestPoint, jd: JulianDate) -> Result<Option<f64>> {
+    let index = point.coordinate - 1;
+    if point.target >= FIRST_ANGLE_TARGET {
+        return Ok(eph
+            .native_state(point.target_name(), jd)?
+            .map(|(values, rates)| [values, rates].concat()[index]));
+    }
+
+    let target = match state(eph, point.target, jd)? {
+        Some(state) => state,
+        None => return Ok(None),
+    };
+    let center = match point.center {
+        0 => None,
+        center => match state(eph, center, jd)? {
+            Some(state) => Some(state),
+            None => return Ok(None),
+        },
+    };
+    let relative = match center {
+        Some(center) => target - center,
+        None => target,
+    };
+    let p = relative.position;
+    let v = relative.velocity;
+    Ok(Some([p.x, p.y, p.z, v.x, v.y, v.z][index]))
+}
+
+/// Barycentric state of a test-point body, or `None` if the ephemeris does
+/// not carry it
+fn state(eph: &Ephemeris, target: u32, jd: JulianDate) -> Result<Option<State>> {
+    let name = TARGETS[target as usize - 1];
+    if name == "Solar_system_barycenter" {
+        let zero = Position::new(0.0, 0.0, 0.0);
+        return Ok(Some(State::new(zero, zero)));
+    }
+    if !eph.covers(name, jd) {
+        return Ok(None);
+    }
+    eph.get_state(name, jd).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ephemeris::tests::write_binary;
+
+    #[test]
+    fn test_verify() {
+        let text = "441\nsome header\nEOT\n\
+             999  2000.01.01 2451545.0 11 12  1  1.0\n\
+             999  2000.01.01 2451545.0 11 12  4  0.0\n\
+             999  2000.01.01 2451545.0 12 11  2  1.0D-12\n\
+             999  2000.01.01 2451545.0  4 12  1  1.5\n\
+             999  1950.01.01 2433282.5 11 12  1  1.0\n";
+        let dir = std::env::temp_dir();
+        let testpo = dir.join(format!("rust_jpl_testpo_{}.999", std::process::id()));
+        let binary = dir.join(format!("rust_jpl_verify_{}.bin", std::process::id()));
+        std::fs::write(&testpo, text).unwrap();
+        write_binary(&binary);
+        let eph = Ephemeris::open(binary.to_str().unwrap()).unwrap();
+        let points = read_test_points(testpo.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&testpo).unwrap();
+        std::fs::remove_file(&binary).unwrap();
+
+        assert_eq!(points.len(), 5);
+        let report = verify(&eph, &points, TOLERANCE).unwrap();
+        assert_eq!(report.skipped, 2);
+        let targets: Vec<(u32, usize)> =
+            report.bodies.iter().map(|b| (b.target, b.points)).collect();
+        assert_eq!(targets, [(11, 2), (12, 1)]);
+        assert!(report.bodies[0].max_deviation < 1e-15);
+        assert!(!report.passed());
+        assert_eq!(report.failures()[0].name, "Solar_system_barycenter");
+
+        assert!(matches!(
+            TestPoint::parse(4, "999  2000.01.01 2451545.0 99 12  1  1.0"),
+            Err(Error::Parse {
+                kind: FileKind::TestPoints,
+                line: 4,
+                ..
+            })
+        ));
+        std::fs::write(&testpo, format!("{}999  2000.01.01\n", text)).unwrap();
+        let truncated = read_test_points(testpo.to_str().unwrap());
+        std::fs::remove_file(&testpo).unwrap();
+        assert!(matches!(
+            truncated.err().unwrap().root(),
+            Error::Parse { line: 9, .. }
+        ));
+    }
+
+    #[test]
+    fn test_compare() {
+        let dir = std::env::temp_dir();
+        let full = dir.join(format!("rust_jpl_reference_{}.bin", std::process::id()));
+        let shifted = dir.join(format!("rust_jpl_shifted_{}.bin", std::process::id()));
+        write_binary(&full);
+        // Move the Sun 2 km along x in the copy
+        let mut bytes = std::fs::read(&full).unwrap();
+        let at = (2 * 398 + 2) * 8;
+        let x = f64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) + 2.0;
+        bytes[at..at + 8].copy_from_slice(&x.to_le_bytes());
+        std::fs::write(&shifted, bytes).unwrap();
+        let reference = Ephemeris::open(full.to_str().unwrap()).unwrap();
+        let candidate = Ephemeris::open(shifted.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&full).unwrap();
+        std::fs::remove_file(&shifted).unwrap();
+
+        let bodies = ["Sun", "Moon_geocentric"];
+        let comparisons =
+            compare(&reference, &candidate, &bodies, 2451536.5, 2451568.5, 4.0).unwrap();
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(comparisons[0].name, "Sun");
+        assert_eq!(comparisons[0].samples, 9);
+        assert!((comparisons[0].max_km - 2.0).abs() < 1e-6);
+        assert_eq!(comparisons[1].max_km, 0.0);
+        assert_eq!(comparisons[1].worst_jd, 2451536.5);
+
+        assert!(matches!(
+            compare(&reference, &candidate, &bodies, 2451540.0, 2451536.5, 1.0),
+            Err(Error::InvalidDate(_))
+        ));
+        assert!(matches!(
+            compare(&reference, &candidate, &["Sun"], 2451536.5, 2451600.0, 1.0),
+            Err(Error::DateOutOfRange { .. })
+        ));
+    }
+}